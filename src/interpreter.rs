@@ -1,159 +1,166 @@
 use std::{
-    fs,
     io::{Error, ErrorKind},
-    process::Output,
+    process::Command,
 };
 
 use crate::{
-    shell::core::{ShellCommandProvider, ShellInterpreter},
+    parser::{self, Connector, Item, List},
+    shell::{
+        core::{ShellCommandProvider, ShellInterpreter},
+        session::{Session, Stream},
+        subshell,
+    },
     tokenizer::Token,
-    util::{error::AsBytes, output::SplitOutput, path::ExecutionPath},
+    util::error::{is_abort, Abort, Status},
 };
 
+mod alias;
+mod assignment;
+mod compound;
+mod expansion;
+mod function;
+mod pipeline;
+mod redirection;
+
 pub struct Interpreter {}
 
 impl ShellInterpreter<Token> for Interpreter {
-    fn run<CP: ShellCommandProvider<Token>>(tokens: &[Token]) -> Result<Vec<u8>, Error> {
-        match tokens.iter().any(|t| t.is_redirection_token()) {
-            true => Self::handle_redirected_input::<CP>(tokens),
-            false => Self::handle_direct_input::<CP>(tokens),
-        }
+    fn run<CP: ShellCommandProvider<Token>>(
+        tokens: &[Token],
+        session: &mut Session,
+    ) -> Result<i32, Error> {
+        let tokens = alias::expand_aliases(tokens, &session.aliases)?;
+        let list = parser::parse(&tokens)?;
+
+        Self::run_list::<CP>(&list, session)?;
+
+        Ok(session.last_status)
+    }
+
+    fn check(tokens: &[Token]) -> Result<(), Error> {
+        parser::parse(tokens).map(|_| ())
+    }
+
+    fn expand<CP: ShellCommandProvider<Token>>(
+        text: &str,
+        session: &mut Session,
+    ) -> Result<String, Error> {
+        expansion::expand_heredoc::<CP>(text, session)
     }
 }
 
 impl Interpreter {
-    fn handle_direct_input<CP: ShellCommandProvider<Token>>(
-        tokens: &[Token],
-    ) -> Result<Vec<u8>, Error> {
-        match tokens.first().unwrap() {
-            Token::Value(cmd) | Token::String(cmd, _) if cmd.get_exec_path().is_some() => {
-                let output = Self::execute_external(tokens, cmd)?;
+    /// Runs the pipelines of a list until a `break` or `continue` jumps out of it. Ctrl-C
+    /// ends the list and every list around it.
+    fn run_list<CP: ShellCommandProvider<Token>>(
+        list: &List,
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        let mut index = 0;
+
+        while let Some(item) = list.items.get(index) {
+            if session.flow.is_some() {
+                break;
+            }
 
-                if output.status.success() {
-                    let mut output_array = output.stdout.to_vec();
+            // An and-or list ending with `&` runs as a whole in a subshell.
+            let result = match and_or_list(&list.items[index..]) {
+                items @ [_, .., last] if last.background => {
+                    index += items.len();
+                    Self::run_background::<CP>(items, session)
+                }
+                _ => {
+                    index += 1;
 
-                    if output_array.last() == Some(&10) {
-                        output_array.pop();
+                    if !item.connector.should_run(session.last_status) {
+                        continue;
                     }
-
-                    return Ok(output_array);
+                    Self::run_pipeline::<CP>(&item.pipeline, item.background, session)
                 }
-
-                let mut error_array = output.stderr.to_vec();
-
-                if error_array.last() == Some(&10) {
-                    error_array.pop();
+            };
+
+            match result {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::Interrupted => return Err(err),
+                Err(err) if is_abort(&err) => return Err(err),
+                Err(err) => {
+                    print_error(session, &err)?;
+                    session.last_status = 1;
                 }
-
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    String::from_utf8(error_array).unwrap(),
-                ));
             }
-            Token::Value(cmd) | Token::String(cmd, _) => match CP::run(cmd, tokens) {
-                Ok(response) => return Ok(response.as_bytes().to_vec()),
-                Err(err) => return Err(err),
-            },
-            _ => return Err(Error::new(ErrorKind::InvalidInput, "error: invalid input")),
+
+            check_interrupted(session)?;
         }
+
+        Ok(())
     }
 
-    fn execute_external(tokens: &[Token], cmd: &String) -> Result<Output, Error> {
+    fn external_command(tokens: &[Token], cmd: &String) -> Command {
         let input_array = tokens
             .iter()
             .skip(2)
             .filter(|i| !matches!(i, Token::Space))
             .map(|i| i.serialize());
 
-        std::process::Command::new(cmd).args(input_array).output()
-    }
-
-    fn handle_redirected_input<CP: ShellCommandProvider<Token>>(
-        tokens: &[Token],
-    ) -> Result<Vec<u8>, Error> {
-        let redirection_index = tokens
-            .iter()
-            .position(|t| t.is_redirection_token())
-            .unwrap();
-
-        let (tokens, redirection_tokens) = tokens.split_at(redirection_index);
-
-        let (response, error) = match tokens.first() {
-            Some(Token::Value(cmd) | Token::String(cmd, _)) if cmd.get_exec_path().is_some() => {
-                let output = Self::execute_external(tokens, cmd)?;
+        let mut command = Command::new(cmd);
+        command.args(input_array);
+        subshell::restore_signals(&mut command);
 
-                output.split_output()
-            }
-            Some(Token::Value(cmd) | Token::String(cmd, _)) => match CP::run(cmd, tokens) {
-                Ok(response) => (Some(response.as_bytes().to_vec()), None),
-                Err(err) => (None, Some(err)),
-            },
-            Some(_) => return Err(Error::new(ErrorKind::InvalidInput, "error: invalid input")),
-            None => return Ok(vec![]),
-        };
-
-        Self::execute_redirected(redirection_tokens, response, error)
+        command
     }
+}
 
-    fn execute_redirected(
-        redirection_tokens: &[Token],
-        output: Option<Vec<u8>>,
-        error: Option<Error>,
-    ) -> Result<Vec<u8>, Error> {
-        let path = redirection_tokens.get(2).unwrap().serialize();
+/// The pipelines from the first of `items` to the end of its and-or list.
+fn and_or_list(items: &[Item]) -> &[Item] {
+    let end = items
+        .iter()
+        .skip(1)
+        .position(|item| item.connector == Connector::Always)
+        .map_or(items.len(), |end| end + 1);
 
-        match redirection_tokens.first().unwrap() {
-            Token::Redirector('1') => {
-                fs::write(path, output.unwrap())?;
+    &items[..end]
+}
 
-                match error {
-                    Some(err) => Err(err),
-                    None => Ok(vec![]),
-                }
-            }
-            Token::Redirector('2') => {
-                fs::write(path, error.unwrap().to_string().as_bytes())?;
+/// Gives up on the rest of the input after a Ctrl-C, whether the shell or the foreground job
+/// got it.
+fn check_interrupted(session: &mut Session) -> Result<(), Error> {
+    if !session.is_interrupted() {
+        return Ok(());
+    }
 
-                match output {
-                    Some(output) => Ok(output),
-                    None => Ok(vec![]),
-                }
-            }
-            Token::Appender('1') => {
-                Self::append_to_file(&path, &output.unwrap())?;
+    session.last_status = 128 + libc::SIGINT;
 
-                match error {
-                    Some(err) => Err(err),
-                    None => Ok(vec![]),
-                }
-            }
-            Token::Appender('2') => {
-                Self::append_to_file(&path, &error.unwrap().as_bytes())?;
+    Err(Error::other(Abort(String::new())))
+}
 
-                match output {
-                    Some(output) => Ok(output),
-                    None => Ok(vec![]),
-                }
-            }
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "error: invalid redirection",
-                ))
-            }
-        }
+fn print_error(session: &Session, err: &Error) -> Result<(), Error> {
+    match error_message(session, err) {
+        // With stderr closed there's nowhere to report it.
+        Some(_) if matches!(session.streams[2], Stream::Closed) => Ok(()),
+        Some(message) => session.write_stderr(format!("{}\n", message).as_bytes()),
+        None => Ok(()),
     }
+}
 
-    fn append_to_file(path: &str, content: &[u8]) -> Result<(), Error> {
-        let mut contents = fs::read(path)?;
-
-        if !contents.is_empty() {
-            contents.extend_from_slice(&[10]);
-        }
+/// The message of an error, with the file and line of the command when it comes from one.
+/// `None` for errors without a message.
+fn error_message(session: &Session, err: &Error) -> Option<String> {
+    let message = err.to_string();
 
-        contents.extend_from_slice(content);
-        fs::write(path, contents)?;
+    match (&session.location, message.is_empty()) {
+        (_, true) => None,
+        (Some(location), false) => Some(format!("{}: {}", location, message)),
+        (None, false) => Some(message),
+    }
+}
 
-        Ok(())
+fn builtin_status(result: &Result<String, Error>) -> i32 {
+    match result {
+        Ok(_) => 0,
+        Err(err) => match err.get_ref().and_then(|e| e.downcast_ref::<Status>()) {
+            Some(Status(status)) => *status,
+            None if err.kind() == ErrorKind::NotFound => 127,
+            None => 1,
+        },
     }
 }
@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, io::Error};
+
+use crate::{
+    shell::{core::ShellTokenizer, variables::split_assignment},
+    tokenizer::{Token, Tokenizer},
+};
+
+/// Reserved words that are followed by another command.
+const COMMAND_PREFIXES: [&str; 9] = [
+    "if", "then", "else", "elif", "while", "until", "do", "{", "!",
+];
+
+/// Replaces the unquoted first word of every command with its alias. The value of an alias
+/// is expanded again, except for aliases already being expanded, so `alias ls='ls -F'` and
+/// cycles end. A value ending in a blank makes the word after it a command word too.
+pub(super) fn expand_aliases(
+    tokens: &[Token],
+    aliases: &BTreeMap<String, String>,
+) -> Result<Vec<Token>, Error> {
+    if aliases.is_empty() {
+        return Ok(tokens.to_vec());
+    }
+
+    let (expanded, _) = expand(tokens, aliases, &mut Vec::new())?;
+
+    Ok(expanded)
+}
+
+/// Returns the tokens and whether the word after them is in command position.
+fn expand(
+    tokens: &[Token],
+    aliases: &BTreeMap<String, String>,
+    active: &mut Vec<String>,
+) -> Result<(Vec<Token>, bool), Error> {
+    let mut expanded = Vec::new();
+    let mut command_word = true;
+    let mut is_target = false;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = &tokens[index];
+
+        if !token.is_word_token() {
+            match token {
+                Token::Space => {}
+                Token::HereDoc(_, _) => {}
+                token if token.is_redirection_token() => is_target = true,
+                // The patterns of a `case` follow `;;`.
+                Token::DoubleSemicolon => command_word = false,
+                Token::Pipe | Token::CloseParen => command_word = true,
+                token if token.is_list_operator() => command_word = true,
+                _ => command_word = false,
+            }
+
+            expanded.push(token.clone());
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+
+        while tokens.get(index).is_some_and(|t| t.is_word_token()) {
+            index += 1;
+        }
+
+        let word = &tokens[start..index];
+
+        if std::mem::take(&mut is_target) || !command_word {
+            expanded.extend_from_slice(word);
+            continue;
+        }
+
+        let alias = match word {
+            [Token::Value(name)] if !active.contains(name) => {
+                aliases.get(name).map(|value| (name, value))
+            }
+            _ => None,
+        };
+
+        let Some((name, value)) = alias else {
+            command_word = match word {
+                [Token::Value(word)] => {
+                    COMMAND_PREFIXES.contains(&word.as_str()) || split_assignment(word).is_some()
+                }
+                _ => false,
+            };
+            expanded.extend_from_slice(word);
+            continue;
+        };
+
+        active.push(name.clone());
+        let result = expand(&Tokenizer::tokenize(value)?, aliases, active);
+        active.pop();
+
+        let (tokens, next_is_command) = result?;
+        expanded.extend(tokens);
+        command_word = next_is_command || value.ends_with([' ', '\t']);
+    }
+
+    Ok((expanded, command_word))
+}
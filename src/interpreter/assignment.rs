@@ -0,0 +1,25 @@
+use crate::{shell::variables::split_assignment, tokenizer::Token};
+
+/// A `NAME=value` word in front of a command.
+pub(super) type Assignment = (String, String);
+
+/// Separates the leading `NAME=value` words from the command and its arguments.
+pub(super) fn split_assignments(words: Vec<Token>) -> (Vec<Assignment>, Vec<Token>) {
+    let mut assignments = Vec::new();
+    let mut iter = words.into_iter().peekable();
+
+    while let Some(Token::Value(word)) = iter.peek() {
+        let Some((name, value)) = split_assignment(word) else {
+            break;
+        };
+
+        assignments.push((name.to_string(), value.to_string()));
+        iter.next();
+
+        if iter.peek() == Some(&Token::Space) {
+            iter.next();
+        }
+    }
+
+    (assignments, iter.collect())
+}
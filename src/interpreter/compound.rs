@@ -0,0 +1,166 @@
+use std::io::Error;
+
+use crate::{
+    parser::{Compound, List},
+    shell::{
+        core::ShellCommandProvider,
+        session::{Flow, Session},
+    },
+    tokenizer::Token,
+    util::pattern,
+};
+
+use super::{check_interrupted, expansion, Interpreter};
+
+impl Interpreter {
+    /// Runs `if`, loops, `case` and `{ ...; }` in the shell itself, so the variables they set
+    /// stay set.
+    pub(super) fn run_compound<CP: ShellCommandProvider<Token>>(
+        compound: &Compound,
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        match compound {
+            Compound::Group(list) => Self::run_list::<CP>(list, session),
+            Compound::If {
+                branches,
+                otherwise,
+            } => {
+                for (condition, body) in branches {
+                    Self::run_list::<CP>(condition, session)?;
+
+                    if session.flow.is_some() {
+                        return Ok(());
+                    }
+
+                    if session.last_status == 0 {
+                        return Self::run_list::<CP>(body, session);
+                    }
+                }
+
+                match otherwise {
+                    Some(list) => Self::run_list::<CP>(list, session),
+                    None => {
+                        session.last_status = 0;
+                        Ok(())
+                    }
+                }
+            }
+            Compound::Loop {
+                until,
+                condition,
+                body,
+            } => {
+                session.loop_depth += 1;
+                let result = Self::run_loop::<CP>(*until, condition, body, session);
+                session.loop_depth -= 1;
+
+                result
+            }
+            Compound::For { name, words, body } => {
+                let words = match words {
+                    Some(words) => expansion::expand_words::<CP>(words, session)?,
+                    None => session.positional.clone(),
+                };
+
+                session.loop_depth += 1;
+                let result = Self::run_for::<CP>(name, &words, body, session);
+                session.loop_depth -= 1;
+
+                result
+            }
+            Compound::Case { word, arms } => {
+                let word = expansion::expand_single::<CP>(word, session)?;
+
+                for arm in arms {
+                    for pattern in &arm.patterns {
+                        let pattern = expansion::expand_pattern::<CP>(pattern, session)?;
+
+                        if pattern::matches(&pattern, &word) {
+                            session.last_status = 0;
+                            return Self::run_list::<CP>(&arm.body, session);
+                        }
+                    }
+                }
+
+                session.last_status = 0;
+                Ok(())
+            }
+        }
+    }
+
+    /// The status of a loop is the one of the last body that ran, 0 if none did.
+    fn run_loop<CP: ShellCommandProvider<Token>>(
+        until: bool,
+        condition: &List,
+        body: &List,
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        let mut status = 0;
+
+        loop {
+            check_interrupted(session)?;
+            Self::run_list::<CP>(condition, session)?;
+
+            if !next_iteration(session) {
+                break;
+            }
+
+            if (session.last_status == 0) == until {
+                break;
+            }
+
+            Self::run_list::<CP>(body, session)?;
+            status = session.last_status;
+
+            if !next_iteration(session) {
+                break;
+            }
+        }
+
+        session.last_status = status;
+
+        Ok(())
+    }
+
+    fn run_for<CP: ShellCommandProvider<Token>>(
+        name: &str,
+        words: &[String],
+        body: &List,
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        session.last_status = 0;
+
+        for word in words {
+            check_interrupted(session)?;
+            session.variables.set(name, word)?;
+            Self::run_list::<CP>(body, session)?;
+
+            if !next_iteration(session) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Handles a `break` or `continue` that reached the loop, and returns whether the loop goes
+/// on. A jump through more loops, or a `return`, is passed on to the enclosing one.
+fn next_iteration(session: &mut Session) -> bool {
+    match session.flow.take() {
+        None | Some(Flow::Continue(1)) => true,
+        Some(Flow::Break(1)) => false,
+        Some(Flow::Break(count)) => {
+            session.flow = Some(Flow::Break(count - 1));
+            false
+        }
+        Some(Flow::Continue(count)) => {
+            session.flow = Some(Flow::Continue(count - 1));
+            false
+        }
+        Some(Flow::Return) => {
+            session.flow = Some(Flow::Return);
+            false
+        }
+    }
+}
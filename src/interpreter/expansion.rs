@@ -0,0 +1,697 @@
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Read},
+    iter::once,
+    process,
+};
+
+use crate::{
+    shell::{
+        core::{ShellCommandProvider, ShellInterpreter, ShellTokenizer},
+        session::{Session, Stream},
+        subshell,
+        variables::split_assignment,
+    },
+    tokenizer::{Token, Tokenizer},
+    util::{error::Abort, glob, pattern},
+};
+
+use super::{print_error, redirection::pipe, Interpreter};
+
+const DEFAULT_IFS: &str = " \t\n";
+
+/// Builtins whose `NAME=value` arguments are expanded like assignments.
+const DECLARATIONS: [&str; 3] = ["export", "local", "readonly"];
+
+/// Expands parameters, splits unquoted expansions on `IFS` and joins adjacent word tokens into
+/// a single argument.
+pub(super) fn expand<CP: ShellCommandProvider<Token>>(
+    tokens: &[Token],
+    session: &mut Session,
+) -> Result<Vec<Token>, Error> {
+    let mut expanded: Vec<Token> = Vec::new();
+    let mut word: Vec<&Token> = Vec::new();
+    let mut command_start = true;
+    let mut declaration = false;
+
+    for token in tokens {
+        if token.is_word_token() {
+            word.push(token);
+            continue;
+        }
+
+        let is_command = command_start;
+        expand_word::<CP>(
+            &word,
+            &mut command_start,
+            declaration,
+            session,
+            &mut expanded,
+        )?;
+
+        if is_command && !command_start {
+            declaration =
+                matches!(word[..], [Token::Value(name)] if DECLARATIONS.contains(&name.as_str()));
+        }
+        word.clear();
+
+        if token == &Token::Pipe {
+            command_start = true;
+            declaration = false;
+        }
+
+        if token == &Token::Space && matches!(expanded.last(), None | Some(Token::Space)) {
+            continue;
+        }
+
+        match token {
+            Token::HereDoc(body, true) => {
+                let body = expand_heredoc::<CP>(body, session)?;
+                expanded.push(Token::HereDoc(body, false));
+            }
+            token => expanded.push(token.clone()),
+        }
+    }
+
+    expand_word::<CP>(
+        &word,
+        &mut command_start,
+        declaration,
+        session,
+        &mut expanded,
+    )?;
+
+    Ok(expanded)
+}
+
+/// Expands a single word. Assignments in front of a command, or given to `export` and the
+/// like, aren't split or globbed, so `A=$B` keeps the value of `B` whole.
+fn expand_word<CP: ShellCommandProvider<Token>>(
+    word: &[&Token],
+    command_start: &mut bool,
+    declaration: bool,
+    session: &mut Session,
+    expanded: &mut Vec<Token>,
+) -> Result<(), Error> {
+    let is_assignment = (*command_start || declaration)
+        && matches!(word.first(), Some(Token::Value(text)) if split_assignment(text).is_some());
+
+    if !word.is_empty() && !is_assignment {
+        *command_start = false;
+    }
+
+    match word {
+        [] => return Ok(()),
+        [Token::Value(text) | Token::Argument(text, _)]
+            if !is_assignment && pattern::has_wildcards(text) => {}
+        [token] if !matches!(token, Token::Variable(_, _) | Token::Substitution(_, _)) => {
+            expanded.push((*token).clone());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let ifs = session
+        .variables
+        .get("IFS")
+        .unwrap_or(DEFAULT_IFS)
+        .to_string();
+    let mut fields = Fields::new(&ifs);
+
+    for token in word {
+        match token {
+            Token::Variable(expression, false) if !is_assignment => {
+                fields.push_split(&expand_parameter::<CP>(expression, session)?)
+            }
+            Token::Variable(expression, true) if expression == "@" && !is_assignment => {
+                fields.push_each(&session.positional)
+            }
+            Token::Variable(expression, _) => {
+                fields.push_quoted(&expand_parameter::<CP>(expression, session)?)
+            }
+            Token::Substitution(command, false) if !is_assignment => {
+                fields.push_split(&substitute::<CP>(command, session)?)
+            }
+            Token::Substitution(command, _) => {
+                fields.push_quoted(&substitute::<CP>(command, session)?)
+            }
+            Token::Value(_) | Token::Argument(_, _) if !is_assignment => {
+                fields.push_unquoted(&token.serialize())
+            }
+            token => fields.push_quoted(&token.serialize()),
+        }
+    }
+
+    let mut values = Vec::new();
+
+    for field in fields.finish() {
+        if !field.has_wildcards {
+            values.push(field.text);
+            continue;
+        }
+
+        let matches = glob::expand(&field.pattern, session.options.globstar);
+
+        match matches.is_empty() {
+            false => values.extend(matches),
+            true if session.options.failglob => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("no match: {}", field.text),
+                ))
+            }
+            true if session.options.nullglob => {}
+            true => values.push(field.text),
+        }
+    }
+
+    for (index, value) in values.into_iter().enumerate() {
+        if index > 0 {
+            expanded.push(Token::Space);
+        }
+        expanded.push(Token::Value(value));
+    }
+
+    Ok(())
+}
+
+/// Runs the command of a `$(...)` in a subshell and returns its output without the trailing
+/// newlines. Nothing it changes in the session, or the working directory, reaches the shell.
+fn substitute<CP: ShellCommandProvider<Token>>(
+    command: &str,
+    session: &mut Session,
+) -> Result<String, Error> {
+    let tokens = Tokenizer::tokenize(command)?;
+    let (reader, writer) = pipe()?;
+
+    let pid = subshell::fork(session, |session| {
+        session.streams[1] = Stream::Fd(writer);
+
+        match Interpreter::run::<CP>(&tokens, session) {
+            Ok(status) => status,
+            Err(err) if err.kind() == ErrorKind::Interrupted => session.last_status,
+            Err(err) => {
+                let _ = print_error(session, &err);
+                1
+            }
+        }
+    });
+
+    // The output ends once the subshell's copy of the write end, the only one left, closes.
+    let mut output = Vec::new();
+    let read = File::from(reader).read_to_end(&mut output);
+    session.last_status = subshell::wait(pid?)?;
+    read?;
+
+    let output = String::from_utf8_lossy(&output);
+
+    Ok(output.trim_end_matches('\n').to_string())
+}
+
+/// Expands the words of a `for` loop into the values it iterates over.
+pub(super) fn expand_words<CP: ShellCommandProvider<Token>>(
+    tokens: &[Token],
+    session: &mut Session,
+) -> Result<Vec<String>, Error> {
+    let mut expanded = Vec::new();
+
+    for word in tokens.split(|token| token == &Token::Space) {
+        let word: Vec<&Token> = word.iter().collect();
+        expand_word::<CP>(&word, &mut false, false, session, &mut expanded)?;
+    }
+
+    Ok(expanded
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Value(value) => Some(value),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Expands a word without field splitting or globbing, like the word of a `case`.
+pub(super) fn expand_single<CP: ShellCommandProvider<Token>>(
+    word: &[Token],
+    session: &mut Session,
+) -> Result<String, Error> {
+    let mut expanded = String::new();
+
+    for token in word {
+        match token {
+            Token::Variable(expression, _) => {
+                expanded.push_str(&expand_parameter::<CP>(expression, session)?)
+            }
+            Token::Substitution(command, _) => {
+                expanded.push_str(&substitute::<CP>(command, session)?)
+            }
+            token => expanded.push_str(&token.serialize()),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Expands a `case` pattern. Quoted parts only match themselves.
+pub(super) fn expand_pattern<CP: ShellCommandProvider<Token>>(
+    word: &[Token],
+    session: &mut Session,
+) -> Result<String, Error> {
+    let mut expanded = String::new();
+
+    for token in word {
+        match token {
+            Token::Value(_) | Token::Argument(_, _) => expanded.push_str(&token.serialize()),
+            Token::Variable(expression, false) => {
+                expanded.push_str(&expand_parameter::<CP>(expression, session)?)
+            }
+            Token::Substitution(command, false) => {
+                expanded.push_str(&substitute::<CP>(command, session)?)
+            }
+            token => expanded.push_str(&pattern::escape(&expand_single::<CP>(
+                std::slice::from_ref(token),
+                session,
+            )?)),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Expands the body of an unquoted here-document, without field splitting or globbing.
+pub(super) fn expand_heredoc<CP: ShellCommandProvider<Token>>(
+    body: &str,
+    session: &mut Session,
+) -> Result<String, Error> {
+    let mut expanded = String::new();
+
+    for token in Tokenizer::tokenize_heredoc(body)? {
+        match token {
+            Token::Variable(expression, _) => {
+                expanded.push_str(&expand_parameter::<CP>(&expression, session)?)
+            }
+            Token::Substitution(command, _) => {
+                expanded.push_str(&substitute::<CP>(&command, session)?)
+            }
+            token => expanded.push_str(&token.serialize()),
+        }
+    }
+
+    Ok(expanded)
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum SplitState {
+    Start,
+    Field,
+    Whitespace,
+    Delimiter,
+}
+
+/// A field with the pattern used for pathname expansion, where the quoted parts are escaped.
+#[derive(Default)]
+struct Field {
+    text: String,
+    pattern: String,
+    has_wildcards: bool,
+}
+
+/// Collects the fields of a single word while unquoted expansions are split on `IFS`.
+struct Fields<'a> {
+    ifs: &'a str,
+    fields: Vec<Field>,
+    current: Field,
+    state: SplitState,
+}
+
+impl<'a> Fields<'a> {
+    fn new(ifs: &'a str) -> Self {
+        Self {
+            ifs,
+            fields: Vec::new(),
+            current: Field::default(),
+            state: SplitState::Start,
+        }
+    }
+
+    fn push_quoted(&mut self, value: &str) {
+        self.current.text.push_str(value);
+        self.current.pattern.push_str(&pattern::escape(value));
+        self.state = SplitState::Field;
+    }
+
+    fn push_unquoted(&mut self, value: &str) {
+        self.current.text.push_str(value);
+        self.current.pattern.push_str(value);
+        self.current.has_wildcards |= pattern::has_wildcards(value);
+        self.state = SplitState::Field;
+    }
+
+    /// `"$@"` gives every positional parameter as a field of its own, and no field at all
+    /// without parameters.
+    fn push_each(&mut self, values: &[String]) {
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                self.finish_field();
+            }
+            self.push_quoted(value);
+        }
+    }
+
+    /// IFS whitespace around a delimiter counts as part of it, other IFS characters delimit
+    /// a field each, so `a::b` with `IFS=:` gives an empty field in the middle.
+    fn push_split(&mut self, value: &str) {
+        for ch in value.chars() {
+            if !self.ifs.contains(ch) {
+                self.push_unquoted(ch.encode_utf8(&mut [0; 4]));
+            } else if ch.is_whitespace() {
+                if self.state == SplitState::Field {
+                    self.finish_field();
+                    self.state = SplitState::Whitespace;
+                }
+            } else {
+                if self.state != SplitState::Whitespace {
+                    self.finish_field();
+                }
+                self.state = SplitState::Delimiter;
+            }
+        }
+    }
+
+    fn finish_field(&mut self) {
+        self.fields.push(std::mem::take(&mut self.current));
+    }
+
+    fn finish(mut self) -> Vec<Field> {
+        if self.state == SplitState::Field {
+            self.finish_field();
+        }
+
+        self.fields
+    }
+}
+
+/// Expands the expression of a `$NAME` or `${...}` reference.
+pub(super) fn expand_parameter<CP: ShellCommandProvider<Token>>(
+    expression: &str,
+    session: &mut Session,
+) -> Result<String, Error> {
+    if let Some(name) = expression.strip_prefix('#').filter(|name| !name.is_empty()) {
+        let value = lookup(name, session).unwrap_or_default();
+        return Ok(value.chars().count().to_string());
+    }
+
+    let (name, operation) = split_name(expression);
+
+    if name.is_empty() {
+        return Err(bad_substitution(expression));
+    }
+
+    let value = lookup(name, session);
+
+    if operation.is_empty() {
+        return Ok(value.unwrap_or_default());
+    }
+
+    let (check_null, rest) = match operation.strip_prefix(':') {
+        Some(rest) => (true, rest),
+        None => (false, operation),
+    };
+
+    let is_unset = match check_null {
+        true => value.as_deref().unwrap_or_default().is_empty(),
+        false => value.is_none(),
+    };
+
+    match rest.chars().next() {
+        Some('-') => match is_unset {
+            true => expand_text::<CP>(&rest[1..], session),
+            false => Ok(value.unwrap_or_default()),
+        },
+        Some('=') => match is_unset {
+            true if is_name(name) => {
+                let value = expand_text::<CP>(&rest[1..], session)?;
+                session.variables.set(name, &value)?;
+                Ok(value)
+            }
+            true => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("${}: cannot assign in this way", name),
+            )),
+            false => Ok(value.unwrap_or_default()),
+        },
+        Some('?') => match is_unset {
+            true => {
+                let message = match &rest[1..] {
+                    "" => String::from("parameter null or not set"),
+                    word => expand_text::<CP>(word, session)?,
+                };
+
+                session.last_status = 1;
+
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    Abort(format!("{}: {}", name, message)),
+                ))
+            }
+            false => Ok(value.unwrap_or_default()),
+        },
+        Some('+') => match is_unset {
+            true => Ok(String::new()),
+            false => expand_text::<CP>(&rest[1..], session),
+        },
+        _ if check_null => Err(bad_substitution(expression)),
+        _ => {
+            let value = value.unwrap_or_default();
+
+            if let Some(pattern) = operation.strip_prefix("##") {
+                Ok(remove_prefix(
+                    &value,
+                    &expand_text::<CP>(pattern, session)?,
+                    true,
+                ))
+            } else if let Some(pattern) = operation.strip_prefix('#') {
+                Ok(remove_prefix(
+                    &value,
+                    &expand_text::<CP>(pattern, session)?,
+                    false,
+                ))
+            } else if let Some(pattern) = operation.strip_prefix("%%") {
+                Ok(remove_suffix(
+                    &value,
+                    &expand_text::<CP>(pattern, session)?,
+                    true,
+                ))
+            } else if let Some(pattern) = operation.strip_prefix('%') {
+                Ok(remove_suffix(
+                    &value,
+                    &expand_text::<CP>(pattern, session)?,
+                    false,
+                ))
+            } else if let Some(rest) = operation.strip_prefix('/') {
+                let (mode, rest) = match rest.chars().next() {
+                    Some('/') => (Replace::All, &rest[1..]),
+                    Some('#') => (Replace::Prefix, &rest[1..]),
+                    Some('%') => (Replace::Suffix, &rest[1..]),
+                    _ => (Replace::First, rest),
+                };
+                let (pattern, replacement) = split_replacement(rest);
+                let pattern = expand_text::<CP>(pattern, session)?;
+                let replacement = expand_text::<CP>(replacement, session)?;
+
+                Ok(replace(&value, &pattern, &replacement, mode))
+            } else {
+                Err(bad_substitution(expression))
+            }
+        }
+    }
+}
+
+/// Expands the word of a `${VAR:-word}` and the like, with its parameters and command
+/// substitutions, but without field splitting or globbing.
+fn expand_text<CP: ShellCommandProvider<Token>>(
+    text: &str,
+    session: &mut Session,
+) -> Result<String, Error> {
+    expand_single::<CP>(&Tokenizer::tokenize_word(text)?, session)
+}
+
+fn lookup(name: &str, session: &Session) -> Option<String> {
+    match name {
+        "?" => Some(session.last_status.to_string()),
+        "$" => Some(process::id().to_string()),
+        "0" => Some(session.name.clone()),
+        "#" => Some(session.positional.len().to_string()),
+        "@" => Some(session.positional.join(" ")),
+        "*" => {
+            let ifs = session.variables.get("IFS").unwrap_or(DEFAULT_IFS);
+            let separator = ifs.chars().next().map(String::from).unwrap_or_default();
+
+            Some(session.positional.join(&separator))
+        }
+        "-" => Some(String::new()),
+        "!" => session.jobs.last_pid.map(|pid| pid.to_string()),
+        name if name.chars().all(|ch| ch.is_ascii_digit()) => name
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| session.positional.get(index.checked_sub(1)?))
+            .cloned(),
+        name => session.variables.get(name).map(|value| value.to_string()),
+    }
+}
+
+fn split_name(expression: &str) -> (&str, &str) {
+    match expression.chars().next() {
+        // `${10}` is the tenth positional parameter, while `$10` is read as `${1}0`.
+        Some(ch) if ch.is_ascii_digit() => {
+            let end = expression
+                .find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(expression.len());
+
+            expression.split_at(end)
+        }
+        Some(ch) if is_special(ch) => expression.split_at(ch.len_utf8()),
+        _ => {
+            let end = expression
+                .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+                .unwrap_or(expression.len());
+
+            expression.split_at(end)
+        }
+    }
+}
+
+fn split_replacement(text: &str) -> (&str, &str) {
+    let mut escaped = false;
+
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '\\' if !escaped => escaped = true,
+            '/' if !escaped => return (&text[..index], &text[index + 1..]),
+            _ => escaped = false,
+        }
+    }
+
+    (text, "")
+}
+
+fn is_special(ch: char) -> bool {
+    ch.is_ascii_digit() || matches!(ch, '?' | '$' | '#' | '@' | '*' | '!' | '-')
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+}
+
+fn bad_substitution(expression: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("${{{}}}: bad substitution", expression),
+    )
+}
+
+fn boundaries(value: &str) -> Vec<usize> {
+    value
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain(once(value.len()))
+        .collect()
+}
+
+fn remove_prefix(value: &str, pattern: &str, longest: bool) -> String {
+    let mut ends = boundaries(value);
+
+    if longest {
+        ends.reverse();
+    }
+
+    match ends
+        .into_iter()
+        .find(|&end| pattern::matches(pattern, &value[..end]))
+    {
+        Some(end) => value[end..].to_string(),
+        None => value.to_string(),
+    }
+}
+
+fn remove_suffix(value: &str, pattern: &str, longest: bool) -> String {
+    let mut starts = boundaries(value);
+
+    if !longest {
+        starts.reverse();
+    }
+
+    match starts
+        .into_iter()
+        .find(|&start| pattern::matches(pattern, &value[start..]))
+    {
+        Some(start) => value[..start].to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Replace {
+    First,
+    All,
+    Prefix,
+    Suffix,
+}
+
+fn replace(value: &str, pattern: &str, replacement: &str, mode: Replace) -> String {
+    if pattern.is_empty() {
+        return value.to_string();
+    }
+
+    let positions = boundaries(value);
+
+    match mode {
+        Replace::Prefix => {
+            return match positions
+                .iter()
+                .rev()
+                .find(|&&end| pattern::matches(pattern, &value[..end]))
+            {
+                Some(&end) => format!("{}{}", replacement, &value[end..]),
+                None => value.to_string(),
+            }
+        }
+        Replace::Suffix => {
+            return match positions
+                .iter()
+                .find(|&&start| pattern::matches(pattern, &value[start..]))
+            {
+                Some(&start) => format!("{}{}", &value[..start], replacement),
+                None => value.to_string(),
+            }
+        }
+        Replace::First | Replace::All => {}
+    }
+
+    let mut result = String::new();
+    let mut index = 0;
+
+    while index + 1 < positions.len() {
+        let start = positions[index];
+        let found = positions[index + 1..]
+            .iter()
+            .rposition(|&end| pattern::matches(pattern, &value[start..end]));
+
+        match found {
+            Some(offset) => {
+                result.push_str(replacement);
+                index += offset + 1;
+
+                if mode == Replace::First {
+                    result.push_str(&value[positions[index]..]);
+                    return result;
+                }
+            }
+            None => {
+                result.push_str(&value[start..positions[index + 1]]);
+                index += 1;
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,69 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    parser::Function,
+    shell::{
+        core::ShellCommandProvider,
+        session::{Flow, Session},
+    },
+    tokenizer::Token,
+};
+
+use super::Interpreter;
+
+/// Calls nested deeper than this fail instead of overflowing the stack.
+const MAX_CALL_DEPTH: usize = 200;
+
+impl Interpreter {
+    /// Runs a function with `args` as its positional parameters. Assignments in front of the
+    /// call are local to it.
+    pub(super) fn call_function<CP: ShellCommandProvider<Token>>(
+        function: &Function,
+        args: Vec<String>,
+        assignments: &[(String, String)],
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        if session.call_depth >= MAX_CALL_DEPTH {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{}: maximum function nesting level exceeded ({})",
+                    function.name, MAX_CALL_DEPTH
+                ),
+            ));
+        }
+
+        let positional = std::mem::replace(&mut session.positional, args);
+        let loop_depth = std::mem::take(&mut session.loop_depth);
+        session.call_depth += 1;
+        session.variables.push_scope();
+
+        let mut result = Ok(());
+
+        for (name, value) in assignments {
+            result = session
+                .variables
+                .make_local(name)
+                .and_then(|()| session.variables.set(name, value));
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if result.is_ok() {
+            result = Self::run_compound::<CP>(&function.body, session);
+        }
+
+        session.variables.pop_scope();
+        session.call_depth -= 1;
+        session.loop_depth = loop_depth;
+        session.positional = positional;
+
+        if session.flow == Some(Flow::Return) {
+            session.flow = None;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    fs::File,
+    io::{self, Error, ErrorKind, Write},
+    os::{fd::OwnedFd, unix::process::CommandExt},
+    rc::Rc,
+};
+
+use rustix::process::Pid;
+
+use crate::{
+    parser::{Command, Compound, Function, Item, List, Pipeline},
+    shell::{
+        core::ShellCommandProvider,
+        jobs::{Job, STOPPED_STATUS},
+        session::Session,
+        subshell,
+    },
+    tokenizer::Token,
+    util::{
+        error::{is_abort, Abort},
+        path::ExecutionPath,
+    },
+};
+
+use super::{
+    assignment::{split_assignments, Assignment},
+    builtin_status, expansion, print_error,
+    redirection::{pipe, split_redirections, stream, Redirections},
+    Interpreter,
+};
+
+/// A pipeline stage after expansion.
+enum Stage<'a> {
+    Simple(Vec<Token>),
+    /// A compound command with its redirections. Its words are expanded as it runs.
+    Compound(&'a Compound, Vec<Token>),
+    Definition(&'a Rc<Function>),
+}
+
+impl Interpreter {
+    /// Runs every stage with its stdout connected to the next stage's stdin over an OS pipe.
+    /// The last stage writes to the shell's stdout, or wherever a compound command or a
+    /// `$(...)` around it redirected it.
+    /// A builtin, a compound command or a function runs in a subshell unless it's the last
+    /// stage, so no thread of the shell has to write into a pipe.
+    /// The external commands and subshells form one job, which goes into the job table when
+    /// it's started in the `background` or stopped with Ctrl-Z.
+    pub(super) fn run_pipeline<CP: ShellCommandProvider<Token>>(
+        pipeline: &Pipeline,
+        background: bool,
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        let mut stages = Vec::new();
+
+        for command in &pipeline.commands {
+            stages.push(match command {
+                Command::Simple(tokens) => Stage::Simple(expansion::expand::<CP>(tokens, session)?),
+                Command::Compound(compound, redirections) => {
+                    Stage::Compound(compound, expansion::expand::<CP>(redirections, session)?)
+                }
+                Command::Function(function) => Stage::Definition(function),
+            });
+        }
+
+        let last = stages.len() - 1;
+        let grouped = background || session.jobs.has_control();
+        let mut pgid = None;
+        let mut input = match background && !session.jobs.has_control() {
+            // Without job control a background job can't be stopped when it reads the terminal.
+            true => Some(OwnedFd::from(File::open("/dev/null")?)),
+            false => stream(session, 0)?,
+        };
+        // The processes of the job with the index of their stage.
+        let mut children: Vec<(usize, u32)> = Vec::new();
+        let mut statuses = vec![0; stages.len()];
+
+        for (index, stage) in stages.iter().enumerate() {
+            let is_last = index == last;
+            let tokens = match stage {
+                Stage::Simple(tokens) | Stage::Compound(_, tokens) => tokens,
+                Stage::Definition(function) => {
+                    session
+                        .functions
+                        .insert(function.name.clone(), Rc::clone(function));
+                    continue;
+                }
+            };
+            let (words, redirections) = split_redirections(tokens)?;
+            let (assignments, words) = split_assignments(words);
+
+            let stdin = match input.take() {
+                Some(stdin) => Some(stdin),
+                None => stream(session, 0)?,
+            };
+            let stdout = match is_last {
+                false => {
+                    let (reader, writer) = pipe()?;
+                    input = Some(reader);
+                    Some(writer)
+                }
+                true => stream(session, 1)?,
+            };
+
+            let mut fds = Redirections::new(stdin, stdout, stream(session, 2)?);
+
+            if let Err(err) = fds.apply(&redirections, session.options.noclobber) {
+                print_error(session, &err)?;
+                statuses[index] = 1;
+                continue;
+            }
+
+            let function = match words.first() {
+                Some(Token::Value(cmd) | Token::String(cmd, _)) => session.functions.get(cmd),
+                _ => None,
+            };
+
+            // A stage in the shell that isn't the last runs in a subshell, so it writes into the
+            // pipe while the next stages read it. In the background the last one does too.
+            let group = grouped.then(|| pgid.unwrap_or(0));
+            let fork = (!is_last || background).then_some((group, &mut input));
+
+            let result = match (stage, function.cloned()) {
+                (Stage::Compound(compound, _), _) => {
+                    Some(Self::run_stage(&mut fds, fork, session, |session| {
+                        Self::run_compound::<CP>(compound, session)
+                    }))
+                }
+                (_, Some(function)) => {
+                    let args = words
+                        .iter()
+                        .skip(1)
+                        .filter(|t| !matches!(t, Token::Space))
+                        .map(|t| t.serialize())
+                        .collect();
+
+                    // The redirections of the definition apply inside the ones of the call.
+                    let redirections = expansion::expand::<CP>(&function.redirections, session)
+                        .and_then(|tokens| Ok(split_redirections(&tokens)?.1))
+                        .and_then(|own| fds.apply(&own, session.options.noclobber));
+
+                    match redirections {
+                        Ok(()) => Some(Self::run_stage(&mut fds, fork, session, |session| {
+                            Self::call_function::<CP>(&function, args, &assignments, session)
+                        })),
+                        Err(err) => Some(Err(err)),
+                    }
+                }
+                (_, None) => match words.first() {
+                    Some(Token::Value(cmd) | Token::String(cmd, _)) if !is_external::<CP>(cmd) => {
+                        Some(Self::run_stage(&mut fds, fork, session, |session| {
+                            Self::run_builtin::<CP>(cmd, &words, &assignments, session)
+                        }))
+                    }
+                    _ => None,
+                },
+            };
+
+            if let Some(result) = result {
+                match result {
+                    Ok(Some(pid)) => {
+                        if pgid.is_none() && grouped {
+                            pgid = Some(pid);
+
+                            if !background {
+                                session.jobs.give_terminal(pid);
+                            }
+                        }
+
+                        children.push((index, pid));
+                    }
+                    Ok(None) => statuses[index] = session.last_status,
+                    Err(err) if err.kind() == ErrorKind::Interrupted && stages.len() == 1 => {
+                        return Err(err)
+                    }
+                    Err(err) if err.kind() == ErrorKind::Interrupted => {
+                        statuses[index] = session.last_status
+                    }
+                    Err(err) if is_abort(&err) => return Err(err),
+                    Err(err) => {
+                        print_error(session, &err)?;
+                        statuses[index] = 1;
+                    }
+                }
+                continue;
+            }
+
+            match words.first() {
+                // Any other command is external, the builtins ran above.
+                Some(Token::Value(cmd) | Token::String(cmd, _)) => {
+                    let mut command = Self::external_command(&words, cmd);
+
+                    command.envs(assignments);
+                    fds.pass_to(&mut command)?;
+
+                    if grouped {
+                        command.process_group(pgid.unwrap_or(0) as i32);
+                    }
+
+                    match command.spawn() {
+                        Ok(child) => {
+                            if pgid.is_none() && grouped {
+                                pgid = Some(child.id());
+
+                                if !background {
+                                    session.jobs.give_terminal(child.id());
+                                }
+                            }
+
+                            children.push((index, child.id()));
+                        }
+                        Err(err) => {
+                            print_error(
+                                session,
+                                &Error::new(err.kind(), format!("{}: {}", cmd, err)),
+                            )?;
+                            statuses[index] = 126;
+                        }
+                    }
+                }
+                None => {
+                    for (name, value) in assignments {
+                        if let Err(err) = session.variables.set(&name, &value) {
+                            print_error(session, &err)?;
+                            statuses[index] = 1;
+                        }
+                    }
+                }
+                Some(_) => return Err(Error::new(ErrorKind::InvalidInput, "error: invalid input")),
+            }
+        }
+
+        let pids: Vec<u32> = children.iter().map(|(_, pid)| *pid).collect();
+        let job = Job::new(&describe(&stages), &pids, grouped);
+
+        if background && !pids.is_empty() {
+            add_background(session, job)?;
+        } else if !pids.is_empty() {
+            match session.jobs.foreground(job)? {
+                Some(exited) => {
+                    for ((index, _), status) in children.iter().zip(exited) {
+                        statuses[*index] = status;
+                    }
+                }
+                // The rest of the input doesn't run after a job is stopped.
+                None => {
+                    session.last_status = STOPPED_STATUS;
+                    return Err(Error::other(Abort(String::new())));
+                }
+            }
+        }
+
+        let status = match session.options.pipefail {
+            true => statuses
+                .iter()
+                .rev()
+                .copied()
+                .find(|s| *s != 0)
+                .unwrap_or(0),
+            false => statuses[last],
+        };
+
+        session.last_status = match pipeline.negated {
+            true => (status == 0) as i32,
+            false => status,
+        };
+
+        Ok(())
+    }
+
+    /// Runs an and-or list ending with `&` in a subshell, which goes into the job table.
+    pub(super) fn run_background<CP: ShellCommandProvider<Token>>(
+        items: &[Item],
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        let mut list = List {
+            items: items.to_vec(),
+        };
+        list.items
+            .iter_mut()
+            .for_each(|item| item.background = false);
+
+        let stdin = match session.jobs.has_control() {
+            true => stream(session, 0)?,
+            // Without job control a background job can't be stopped when it reads the terminal.
+            false => Some(OwnedFd::from(File::open("/dev/null")?)),
+        };
+        let mut fds = Redirections::new(stdin, stream(session, 1)?, stream(session, 2)?);
+
+        let pid = Self::run_stage(&mut fds, Some((Some(0), &mut None)), session, |session| {
+            Self::run_list::<CP>(&list, session)
+        })?;
+
+        let pids: Vec<u32> = pid.into_iter().collect();
+        add_background(session, Job::new(&list.to_string(), &pids, true))?;
+        session.last_status = 0;
+
+        Ok(())
+    }
+
+    /// Runs a builtin, writing its output and its error to the streams of its stage. A failed
+    /// write is reported like bash does, with status 1.
+    /// The `assignments` before it are set and exported while it runs, and after a special
+    /// builtin they stay.
+    fn run_builtin<CP: ShellCommandProvider<Token>>(
+        cmd: &str,
+        words: &[Token],
+        assignments: &[Assignment],
+        session: &mut Session,
+    ) -> Result<(), Error> {
+        let mut saved = Vec::new();
+
+        for (name, value) in assignments {
+            let assigned = match CP::is_special(cmd) {
+                true => session.variables.set(name, value),
+                false => session
+                    .variables
+                    .set_for_command(name, value)
+                    .map(|old| saved.push(old)),
+            };
+
+            if let Err(err) = assigned {
+                for old in saved.into_iter().rev() {
+                    session.variables.restore(old);
+                }
+                let _ = print_error(session, &err);
+                session.last_status = 1;
+                return Ok(());
+            }
+        }
+
+        let result = CP::run(cmd, words, session);
+
+        for old in saved.into_iter().rev() {
+            session.variables.restore(old);
+        }
+
+        // `exit` leaves with the status it set.
+        if matches!(&result, Err(err) if err.kind() == ErrorKind::Interrupted) {
+            return result.map(|_| ());
+        }
+        session.last_status = builtin_status(&result);
+
+        let written = match result {
+            Ok(response) if response.is_empty() => return Ok(()),
+            Ok(response) => session.write_stdout(format!("{}\n", response).as_bytes()),
+            Err(err) => {
+                // Nothing is left to report an error about the stderr to.
+                let _ = print_error(session, &err);
+                return Ok(());
+            }
+        };
+
+        if let Err(err) = written {
+            let err = Error::new(err.kind(), format!("{}: write error: {}", cmd, err));
+            let _ = print_error(session, &err);
+            session.last_status = 1;
+        }
+
+        Ok(())
+    }
+
+    /// Runs a builtin, a compound command or a function with its redirected streams. With
+    /// `fork`, the process group to join and the read end of the stage's own output pipe, it
+    /// runs in a subshell whose pid is returned, otherwise in the shell itself.
+    fn run_stage(
+        fds: &mut Redirections,
+        fork: Option<(Option<u32>, &mut Option<OwnedFd>)>,
+        session: &mut Session,
+        run: impl FnOnce(&mut Session) -> Result<(), Error>,
+    ) -> Result<Option<u32>, Error> {
+        let streams = fds.take_streams();
+
+        let Some((group, reader)) = fork else {
+            let outer = std::mem::replace(&mut session.streams, streams);
+            let result = run(session);
+            session.streams = outer;
+
+            return result.map(|()| None);
+        };
+
+        let pid = subshell::fork(session, |session| {
+            // The stage would never see the reader go away while it holds the read end too.
+            drop(reader.take());
+
+            if let Some(pgid) = group {
+                let _ = rustix::process::setpgid(None, Pid::from_raw(pgid as i32));
+            }
+
+            session.streams = streams;
+
+            match run(session) {
+                Ok(()) => session.last_status,
+                Err(err) if err.kind() == ErrorKind::Interrupted => session.last_status,
+                Err(err) => {
+                    let _ = print_error(session, &err);
+                    1
+                }
+            }
+        })?;
+
+        // Set from both sides, so it's in place whichever process gets there first.
+        if let Some(pgid) = group {
+            let leader = Pid::from_raw(pgid as i32).or(Pid::from_raw(pid as i32));
+            let _ = rustix::process::setpgid(Pid::from_raw(pid as i32), leader);
+        }
+
+        Ok(Some(pid))
+    }
+}
+
+/// Puts a job started in the background into the table, for `$!` and `jobs`.
+fn add_background(session: &mut Session, job: Job) -> Result<(), Error> {
+    session.jobs.last_pid = job.last_pid();
+    let last_pid = job.last_pid().unwrap_or_default();
+    let id = session.jobs.add(job);
+
+    if session.jobs.has_control() {
+        writeln!(io::stderr(), "[{}] {}", id, last_pid)?;
+    }
+
+    Ok(())
+}
+
+/// The command line a job is listed with.
+fn describe(stages: &[Stage<'_>]) -> String {
+    stages
+        .iter()
+        .filter_map(|stage| match stage {
+            Stage::Simple(tokens) => Some(tokens.iter().map(|t| t.serialize()).collect()),
+            Stage::Compound(_, _) | Stage::Definition(_) => None,
+        })
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+/// Whether the command is a program found on the `PATH` rather than a builtin.
+fn is_external<CP: ShellCommandProvider<Token>>(cmd: &String) -> bool {
+    !CP::get_commands().contains(&cmd.as_str()) && cmd.get_exec_path().is_some()
+}
@@ -0,0 +1,313 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Error, ErrorKind, Write},
+    os::{
+        fd::{AsFd, OwnedFd},
+        unix::fs::OpenOptionsExt,
+    },
+    path::Path,
+    process::{self, Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rustix::{pipe::PipeFlags, process::Resource};
+
+use crate::{
+    shell::session::{Session, Stream},
+    tokenizer::Token,
+};
+
+/// A redirection operator with the path it points to.
+pub(super) type Redirection = (Token, String);
+
+/// The file descriptors of a command, by number. `None` is a closed descriptor.
+pub(super) struct Redirections {
+    fds: Vec<Option<OwnedFd>>,
+}
+
+/// Separates redirection operators and their targets from the words of a command.
+pub(super) fn split_redirections(
+    tokens: &[Token],
+) -> Result<(Vec<Token>, Vec<Redirection>), Error> {
+    let mut words: Vec<Token> = Vec::new();
+    let mut redirections = Vec::new();
+    let mut iter = tokens.iter().peekable();
+
+    while let Some(token) = iter.next() {
+        if !token.is_redirection_token() {
+            let is_repeated_space =
+                token == &Token::Space && matches!(words.last(), None | Some(Token::Space));
+
+            if !is_repeated_space {
+                words.push(token.clone());
+            }
+            continue;
+        }
+
+        if let Token::HereDoc(body, _) = token {
+            redirections.push((token.clone(), body.clone()));
+            continue;
+        }
+
+        if iter.peek() == Some(&&Token::Space) {
+            iter.next();
+        }
+
+        match iter.next() {
+            Some(target) if target.is_word_token() => {
+                redirections.push((token.clone(), target.serialize()))
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "syntax error near unexpected token `newline'",
+                ))
+            }
+        }
+    }
+
+    if words.last() == Some(&Token::Space) {
+        words.pop();
+    }
+
+    Ok((words, redirections))
+}
+
+impl Redirections {
+    pub(super) fn new(
+        stdin: Option<OwnedFd>,
+        stdout: Option<OwnedFd>,
+        stderr: Option<OwnedFd>,
+    ) -> Self {
+        Redirections {
+            fds: vec![stdin, stdout, stderr],
+        }
+    }
+
+    /// Points `fd` to `file`, making room for it in the table.
+    fn set(&mut self, fd: usize, file: Option<OwnedFd>) {
+        if fd >= self.fds.len() {
+            self.fds.resize_with(fd + 1, || None);
+        }
+
+        self.fds[fd] = file;
+    }
+
+    /// Takes the descriptor out of the table, leaving it closed.
+    pub(super) fn take(&mut self, fd: usize) -> Option<OwnedFd> {
+        self.fds.get_mut(fd)?.take()
+    }
+
+    /// Takes the standard streams out of the table for the session, the closed ones as
+    /// `Stream::Closed`.
+    pub(super) fn take_streams(&mut self) -> [Stream; 3] {
+        [0, 1, 2].map(|fd| match self.take(fd) {
+            Some(fd) => Stream::Fd(fd),
+            None => Stream::Closed,
+        })
+    }
+
+    /// Applies the redirections left to right, so `2>&1 > file` sends stderr to where stdout
+    /// pointed before it was redirected.
+    pub(super) fn apply(
+        &mut self,
+        redirections: &[Redirection],
+        noclobber: bool,
+    ) -> Result<(), Error> {
+        for (token, target) in redirections {
+            let fd = token.redirection_fd();
+
+            // The OS takes no descriptor from the open file limit up, what `ulimit -n` shows.
+            if fd.is_some_and(|fd| fd >= open_file_limit()) {
+                return Err(bad_descriptor(&fd.unwrap_or_default().to_string()));
+            }
+
+            let opened = match token {
+                Token::Redirector(_) if noclobber && is_regular_file(target) => {
+                    return Err(Error::new(
+                        ErrorKind::AlreadyExists,
+                        format!("{}: cannot overwrite existing file", target),
+                    ))
+                }
+                Token::Redirector(_) | Token::Clobber(_) | Token::BothRedirector => {
+                    File::create(target)
+                }
+                Token::Appender(_) | Token::BothAppender => {
+                    OpenOptions::new().append(true).create(true).open(target)
+                }
+                Token::Reader(_) => File::open(target),
+                Token::ReadWriter(_) => OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(target),
+                Token::HereDoc(body, _) => {
+                    self.set(0, Some(feed(body.clone().into_bytes())?));
+                    continue;
+                }
+                Token::HereString => {
+                    self.set(0, Some(feed(format!("{}\n", target).into_bytes())?));
+                    continue;
+                }
+                Token::Duplicator(_) | Token::InputDuplicator(_) => {
+                    let file = self.duplicate(target)?;
+                    self.set(fd.unwrap_or_default(), file);
+                    continue;
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "error: invalid redirection",
+                    ))
+                }
+            };
+
+            let file =
+                opened.map_err(|err| Error::new(err.kind(), format!("{}: {}", target, err)))?;
+
+            match fd {
+                Some(fd) => self.set(fd, Some(file.into())),
+                None => {
+                    self.set(2, Some(file.as_fd().try_clone_to_owned()?));
+                    self.set(1, Some(file.into()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hands the descriptors to an external command: the standard streams through `Stdio`,
+    /// the ones above 2 copied onto their number in the child. A closed standard stream is
+    /// closed in the child as well.
+    pub(super) fn pass_to(&mut self, command: &mut Command) -> Result<(), Error> {
+        let mut closed = Vec::new();
+        let mut extra = Vec::new();
+
+        for fd in 0..=2 {
+            let stdio = match self.take(fd) {
+                Some(fd) => Stdio::from(fd),
+                None => {
+                    closed.push(fd as i32);
+                    Stdio::null()
+                }
+            };
+
+            match fd {
+                0 => command.stdin(stdio),
+                1 => command.stdout(stdio),
+                _ => command.stderr(stdio),
+            };
+        }
+
+        let above = self.fds.len() as i32;
+
+        for fd in 3..self.fds.len() {
+            if let Some(source) = self.take(fd) {
+                // Moved above every target, so no copy overwrites a source still to be copied.
+                let source = rustix::io::fcntl_dupfd_cloexec(&source, above)?;
+                extra.push((fd as i32, source));
+            }
+        }
+
+        if closed.is_empty() && extra.is_empty() {
+            return Ok(());
+        }
+
+        shell_sys::move_fds_on_exec(command, extra, closed);
+
+        Ok(())
+    }
+
+    /// The copy of `target` for `n>&target`, where `-` closes the descriptor.
+    fn duplicate(&self, target: &str) -> Result<Option<OwnedFd>, Error> {
+        if target == "-" {
+            return Ok(None);
+        }
+
+        let source = target
+            .parse::<usize>()
+            .ok()
+            .and_then(|fd| self.fds.get(fd)?.as_ref())
+            .ok_or_else(|| bad_descriptor(target))?;
+
+        Ok(Some(source.try_clone()?))
+    }
+}
+
+/// Creates an OS pipe, returning its read and write ends.
+pub(super) fn pipe() -> Result<(OwnedFd, OwnedFd), Error> {
+    Ok(rustix::pipe::pipe_with(PipeFlags::CLOEXEC)?)
+}
+
+/// A duplicate of one of the shell's own standard streams.
+pub(super) fn inherit(stream: impl AsFd) -> Result<OwnedFd, Error> {
+    stream.as_fd().try_clone_to_owned()
+}
+
+/// A duplicate of a standard stream of the commands being run, the one a compound command or
+/// a `$(...)` redirected it to, or else the shell's own. `None` when it's closed.
+pub(super) fn stream(session: &Session, fd: usize) -> Result<Option<OwnedFd>, Error> {
+    let stream = match (&session.streams[fd], fd) {
+        (Stream::Fd(stream), _) => stream.try_clone(),
+        (Stream::Closed, _) => return Ok(None),
+        (Stream::Inherited, 0) => inherit(io::stdin()),
+        (Stream::Inherited, 1) => inherit(io::stdout()),
+        (Stream::Inherited, _) => inherit(io::stderr()),
+    };
+
+    stream.map(Some)
+}
+
+/// A file holding `bytes` to read from, for a here-document, like bash does. It's removed
+/// right away and goes away with the last descriptor, and unlike a pipe nothing has to keep
+/// writing into it while the command reads.
+pub(super) fn feed(bytes: Vec<u8>) -> Result<OwnedFd, Error> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let (path, mut writer) = loop {
+        let name = format!(
+            "shell-heredoc-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = env::temp_dir().join(name);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+        {
+            Ok(file) => break (path, file),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    };
+
+    let reader = File::open(&path);
+    fs::remove_file(&path)?;
+    writer.write_all(&bytes)?;
+
+    Ok(reader?.into())
+}
+
+/// The soft limit on open files of the shell, one above the highest descriptor it can open.
+fn open_file_limit() -> usize {
+    let limit = rustix::process::getrlimit(Resource::Nofile).current;
+
+    limit.map_or(usize::MAX, |limit| limit.try_into().unwrap_or(usize::MAX))
+}
+
+fn is_regular_file(path: &str) -> bool {
+    fs::metadata(Path::new(path)).is_ok_and(|metadata| metadata.is_file())
+}
+
+fn bad_descriptor(fd: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("{}: Bad file descriptor", fd),
+    )
+}
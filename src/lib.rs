@@ -5,9 +5,12 @@
 pub mod tokenizer;
 pub mod util {
     pub mod error;
-    pub mod output;
+    pub mod glob;
     pub mod path;
+    pub mod pattern;
+    pub mod unicode;
 }
 pub mod interpreter;
+pub mod parser;
 pub mod provider;
 pub mod shell;
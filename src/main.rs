@@ -1,15 +1,67 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    os::fd::AsFd,
+    process,
+};
+
 use shell_starter_rust::{
     interpreter::Interpreter,
     provider::CommandProvider,
-    shell::Shell,
+    shell::{
+        args::{Arguments, Mode},
+        script::Script,
+        session::Session,
+        Shell,
+    },
     tokenizer::{Token, Tokenizer},
 };
 
-#[tokio::main]
+#[tokio::main(flavor = "current_thread")]
 async fn main() {
-    let mut shell = Shell::new();
+    let arguments = match Arguments::parse(std::env::args()) {
+        Ok(arguments) => arguments,
+        Err(err) => {
+            eprintln!("shell: {}", err);
+            process::exit(2);
+        }
+    };
+
+    let startup_files = arguments.startup_files();
+    let mut session = Session::new();
+    session.name = arguments.name;
+    session.positional = arguments.positional;
+
+    let status = match arguments.mode {
+        Mode::Interactive => {
+            let mut shell = Shell::with_session(session).with_startup_files(startup_files);
+
+            let _ = shell
+                .run::<Token, Interpreter, Tokenizer, CommandProvider>()
+                .await;
+
+            shell.last_status()
+        }
+        Mode::Command(command) => Script::new(session)
+            .run::<Token, Interpreter, Tokenizer, CommandProvider>(command.as_bytes()),
+        Mode::Script(path) => match File::open(&path) {
+            Ok(file) => Script::new(session)
+                .run::<Token, Interpreter, Tokenizer, CommandProvider>(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("shell: {}: {}", path.display(), err);
+                127
+            }
+        },
+        // Stdin isn't buffered, so commands can read what comes after their own line.
+        Mode::Stdin => match io::stdin().as_fd().try_clone_to_owned() {
+            Ok(stdin) => Script::new(session)
+                .run::<Token, Interpreter, Tokenizer, CommandProvider>(File::from(stdin)),
+            Err(err) => {
+                eprintln!("shell: {}", err);
+                1
+            }
+        },
+    };
 
-    let _ = shell
-        .run::<Token, Interpreter, Tokenizer, CommandProvider>()
-        .await;
+    process::exit(status);
 }
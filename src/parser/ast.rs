@@ -0,0 +1,94 @@
+use std::rc::Rc;
+
+use crate::tokenizer::Token;
+
+/// Commands joined by `;`, `&`, `&&` and `||`, or newlines.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct List {
+    pub items: Vec<Item>,
+}
+
+/// A pipeline of a list, with how it's joined to the one before it.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Item {
+    pub connector: Connector,
+    pub pipeline: Pipeline,
+    /// Ends with `&`, the shell doesn't wait for it.
+    pub background: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Connector {
+    Always,
+    And,
+    Or,
+}
+
+impl Connector {
+    pub fn should_run(&self, last_status: i32) -> bool {
+        match self {
+            Connector::Always => true,
+            Connector::And => last_status == 0,
+            Connector::Or => last_status != 0,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Pipeline {
+    /// Starts with `!`, which inverts the status.
+    pub negated: bool,
+    pub commands: Vec<Command>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Command {
+    /// The tokens of a command with its arguments, assignments and redirections.
+    Simple(Vec<Token>),
+    /// A compound command with the redirections after it, like `done < file`.
+    Compound(Compound, Vec<Token>),
+    /// `name() compound` or `function name compound`, which defines the function.
+    Function(Rc<Function>),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub body: Compound,
+    /// Redirections after the body, applied every time the function runs.
+    pub redirections: Vec<Token>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Compound {
+    /// `{ list; }`
+    Group(List),
+    /// `if` and every `elif` with their conditions, then the `else` part.
+    If {
+        branches: Vec<(List, List)>,
+        otherwise: Option<List>,
+    },
+    /// `while` or, with `until`, a loop that runs while the condition fails.
+    Loop {
+        until: bool,
+        condition: List,
+        body: List,
+    },
+    /// `for name in words; do body; done`, without `in` the words are `"$@"`.
+    For {
+        name: String,
+        words: Option<Vec<Token>>,
+        body: List,
+    },
+    /// `case word in pattern | pattern) list;; esac`
+    Case {
+        word: Vec<Token>,
+        arms: Vec<CaseArm>,
+    },
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CaseArm {
+    pub patterns: Vec<Vec<Token>>,
+    pub body: List,
+}
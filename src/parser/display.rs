@@ -0,0 +1,229 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::{shell::variables::quote, tokenizer::Token};
+
+use super::{Command, Compound, Connector, Function, List, Pipeline};
+
+const INDENT: &str = "    ";
+
+/// Shows the definition the way it can be read back, one command per line.
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        definition(f, self, 0)
+    }
+}
+
+/// Shows the list on one line, the way `jobs` lists a list run in the background.
+impl Display for List {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        inline(f, self, 0)
+    }
+}
+
+/// Like bash, the braces of the body go on lines of their own.
+fn definition(f: &mut Formatter<'_>, function: &Function, depth: usize) -> Result {
+    match function.body {
+        Compound::Group(_) => write!(f, "{} ()\n{}", function.name, INDENT.repeat(depth))?,
+        _ => write!(f, "{} () ", function.name)?,
+    }
+
+    compound(f, &function.body, depth)?;
+    redirections(f, &function.redirections)
+}
+
+fn list(f: &mut Formatter<'_>, list: &List, depth: usize) -> Result {
+    for (index, item) in list.items.iter().enumerate() {
+        match item.connector {
+            Connector::Always if index > 0 => write!(f, "\n{}", INDENT.repeat(depth))?,
+            Connector::Always => write!(f, "{}", INDENT.repeat(depth))?,
+            Connector::And => write!(f, " && ")?,
+            Connector::Or => write!(f, " || ")?,
+        }
+
+        pipeline(f, &item.pipeline, depth)?;
+
+        if item.background {
+            write!(f, " &")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A list on the line of its keyword, like the condition of an `if`.
+fn inline(f: &mut Formatter<'_>, list: &List, depth: usize) -> Result {
+    for (index, item) in list.items.iter().enumerate() {
+        match item.connector {
+            Connector::Always if index > 0 => write!(f, "; ")?,
+            Connector::Always => {}
+            Connector::And => write!(f, " && ")?,
+            Connector::Or => write!(f, " || ")?,
+        }
+
+        pipeline(f, &item.pipeline, depth)?;
+
+        if item.background {
+            write!(f, " &")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn pipeline(f: &mut Formatter<'_>, pipeline: &Pipeline, depth: usize) -> Result {
+    if pipeline.negated {
+        write!(f, "! ")?;
+    }
+
+    for (index, command) in pipeline.commands.iter().enumerate() {
+        if index > 0 {
+            write!(f, " | ")?;
+        }
+
+        match command {
+            Command::Simple(tokens) => words(f, tokens)?,
+            Command::Compound(body, tokens) => {
+                compound(f, body, depth)?;
+                redirections(f, tokens)?;
+            }
+            Command::Function(function) => definition(f, function, depth)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn compound(f: &mut Formatter<'_>, compound: &Compound, depth: usize) -> Result {
+    let indent = INDENT.repeat(depth);
+
+    match compound {
+        Compound::Group(body) => {
+            writeln!(f, "{{")?;
+            list(f, body, depth + 1)?;
+            write!(f, "\n{}}}", indent)
+        }
+        Compound::If {
+            branches,
+            otherwise,
+        } => {
+            for (index, (condition, body)) in branches.iter().enumerate() {
+                match index {
+                    0 => write!(f, "if ")?,
+                    _ => write!(f, "\n{}elif ", indent)?,
+                }
+                inline(f, condition, depth)?;
+                writeln!(f, "; then")?;
+                list(f, body, depth + 1)?;
+            }
+
+            if let Some(otherwise) = otherwise {
+                writeln!(f, "\n{}else", indent)?;
+                list(f, otherwise, depth + 1)?;
+            }
+
+            write!(f, "\n{}fi", indent)
+        }
+        Compound::Loop {
+            until,
+            condition,
+            body,
+        } => {
+            write!(f, "{} ", if *until { "until" } else { "while" })?;
+            inline(f, condition, depth)?;
+            writeln!(f, "; do")?;
+            list(f, body, depth + 1)?;
+            write!(f, "\n{}done", indent)
+        }
+        Compound::For {
+            name,
+            words: list_words,
+            body,
+        } => {
+            write!(f, "for {}", name)?;
+
+            if let Some(tokens) = list_words {
+                write!(f, " in ")?;
+                words(f, tokens)?;
+            }
+
+            writeln!(f, "; do")?;
+            list(f, body, depth + 1)?;
+            write!(f, "\n{}done", indent)
+        }
+        Compound::Case { word, arms } => {
+            write!(f, "case ")?;
+            words(f, word)?;
+            write!(f, " in")?;
+
+            for arm in arms {
+                write!(f, "\n{}{}", indent, INDENT)?;
+
+                for (index, pattern) in arm.patterns.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " | ")?;
+                    }
+                    words(f, pattern)?;
+                }
+
+                writeln!(f, ")")?;
+                list(f, &arm.body, depth + 2)?;
+                write!(f, "\n{}{};;", indent, INDENT)?;
+            }
+
+            write!(f, "\n{}esac", indent)
+        }
+    }
+}
+
+fn redirections(f: &mut Formatter<'_>, tokens: &[Token]) -> Result {
+    if !tokens.is_empty() {
+        write!(f, " ")?;
+    }
+
+    words(f, tokens)
+}
+
+/// Writes tokens back as source, with the quotes the tokenizer took away. The double-quoted
+/// parts of a word share one pair of quotes.
+fn words(f: &mut Formatter<'_>, tokens: &[Token]) -> Result {
+    let mut quoted = false;
+
+    for token in tokens {
+        let is_double = matches!(
+            token,
+            Token::String(_, true) | Token::Variable(_, true) | Token::Substitution(_, true)
+        );
+
+        if is_double != quoted {
+            write!(f, "\"")?;
+            quoted = is_double;
+        }
+
+        match token {
+            Token::String(text, true) => write!(f, "{}", escape_double(text))?,
+            Token::String(text, false) => write!(f, "{}", quote(text))?,
+            Token::Variable(expression, _) => write!(f, "${{{}}}", expression)?,
+            Token::HereDoc(body, _) => write!(f, "<<< {}", quote(body.trim_end_matches('\n')))?,
+            token => write!(f, "{}", token)?,
+        }
+    }
+
+    if quoted {
+        write!(f, "\"")?;
+    }
+
+    Ok(())
+}
+
+fn escape_double(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        if matches!(ch, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
@@ -0,0 +1,530 @@
+use std::{
+    io::{Error, ErrorKind},
+    rc::Rc,
+};
+
+use crate::{shell::variables::is_valid_name, tokenizer::Token};
+
+pub use ast::{CaseArm, Command, Compound, Connector, Function, Item, List, Pipeline};
+
+mod ast;
+mod display;
+
+/// Words that start or end a compound command when they're the first word of a command.
+const RESERVED: [&str; 15] = [
+    "if", "then", "elif", "else", "fi", "while", "until", "do", "done", "for", "case", "esac", "{",
+    "}", "function",
+];
+
+/// Builds the command list of the tokens. Input that stops in the middle of a command, like
+/// an `if` without its `fi`, fails with `UnexpectedEof` so more lines can be read.
+pub fn parse(tokens: &[Token]) -> Result<List, Error> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+
+    parser.skip_spaces();
+
+    // Newlines are read as `;`, but the input is trimmed, so this one was typed.
+    if let Some(token @ Token::Semicolon) = parser.peek() {
+        return Err(unexpected(token));
+    }
+
+    let list = parser.list(&[])?;
+
+    match parser.peek() {
+        None => Ok(list),
+        Some(token) => Err(unexpected(token)),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(&Token::Space) {
+            self.position += 1;
+        }
+    }
+
+    /// Skips spaces and the `;` that newlines turn into.
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(Token::Space | Token::Semicolon)) {
+            self.position += 1;
+        }
+    }
+
+    /// Whether the next token is the unquoted word `word` on its own.
+    fn is_word(&self, word: &str) -> bool {
+        let is_next_word = matches!(self.peek(), Some(Token::Value(value)) if value == word);
+        let is_joined = self
+            .tokens
+            .get(self.position + 1)
+            .is_some_and(|t| t.is_word_token());
+
+        is_next_word && !is_joined
+    }
+
+    fn reserved(&self) -> Option<&'static str> {
+        RESERVED.into_iter().find(|word| self.is_word(word))
+    }
+
+    /// The end of the input means the command continues on the next line, anything else is
+    /// out of place.
+    fn unexpected(&self) -> Error {
+        match self.peek() {
+            Some(token) => unexpected(token),
+            None => incomplete(),
+        }
+    }
+
+    /// Consumes one of the reserved words in `expected`.
+    fn keyword(&mut self, expected: &[&'static str]) -> Result<&'static str, Error> {
+        self.skip_separators();
+
+        match self.reserved() {
+            Some(word) if expected.contains(&word) => {
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Reads commands until one of the `stop` words, a `;;`, a `)` or the end of the input.
+    fn list(&mut self, stop: &[&str]) -> Result<List, Error> {
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_separators();
+
+            if matches!(
+                self.peek(),
+                None | Some(Token::DoubleSemicolon | Token::CloseParen)
+            ) {
+                break;
+            }
+
+            if self.reserved().is_some_and(|word| stop.contains(&word)) {
+                break;
+            }
+
+            let start = items.len();
+            self.and_or(&mut items)?;
+            self.skip_spaces();
+
+            match self.peek() {
+                Some(Token::Semicolon) => self.position += 1,
+                // The `&` goes with the last pipeline, the whole and-or list runs in the background.
+                Some(Token::Background) => {
+                    self.position += 1;
+                    if let Some(item) = items[start..].last_mut() {
+                        item.background = true;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        Ok(List { items })
+    }
+
+    /// A list that has to contain at least one command, like the body of a loop.
+    fn body(&mut self, stop: &[&str]) -> Result<List, Error> {
+        let list = self.list(stop)?;
+
+        match list.items.is_empty() {
+            true => Err(self.unexpected()),
+            false => Ok(list),
+        }
+    }
+
+    fn and_or(&mut self, items: &mut Vec<Item>) -> Result<(), Error> {
+        let mut connector = Connector::Always;
+
+        loop {
+            items.push(Item {
+                connector,
+                pipeline: self.pipeline()?,
+                background: false,
+            });
+            self.skip_spaces();
+
+            connector = match self.peek() {
+                Some(Token::And) => Connector::And,
+                Some(Token::Or) => Connector::Or,
+                _ => return Ok(()),
+            };
+
+            self.position += 1;
+            self.skip_separators();
+        }
+    }
+
+    fn pipeline(&mut self) -> Result<Pipeline, Error> {
+        self.skip_spaces();
+
+        let negated = self.is_word("!");
+
+        if negated {
+            self.position += 1;
+            self.skip_spaces();
+        }
+
+        let mut commands = vec![self.command()?];
+
+        loop {
+            self.skip_spaces();
+
+            if self.peek() != Some(&Token::Pipe) {
+                break;
+            }
+
+            self.position += 1;
+            self.skip_separators();
+            commands.push(self.command()?);
+        }
+
+        Ok(Pipeline { negated, commands })
+    }
+
+    fn command(&mut self) -> Result<Command, Error> {
+        self.skip_spaces();
+
+        let compound = match self.reserved() {
+            None if self.is_definition() => return self.function(),
+            None => return self.simple(),
+            Some("function") => return self.function(),
+            Some("if") => self.if_clause()?,
+            Some(word @ ("while" | "until")) => self.loop_clause(word == "until")?,
+            Some("for") => self.for_clause()?,
+            Some("case") => self.case_clause()?,
+            Some("{") => {
+                self.position += 1;
+                let body = self.body(&["}"])?;
+                self.keyword(&["}"])?;
+
+                Compound::Group(body)
+            }
+            Some(_) => return Err(self.unexpected()),
+        };
+
+        Ok(Command::Compound(compound, self.redirections()?))
+    }
+
+    /// Whether the command starts with `name()`.
+    fn is_definition(&self) -> bool {
+        let is_name = matches!(self.peek(), Some(Token::Value(name)) if is_valid_name(name));
+        let rest = self.tokens[self.position..]
+            .iter()
+            .skip(1)
+            .filter(|t| t != &&Token::Space);
+
+        is_name && rest.take(1).eq([&Token::OpenParen])
+    }
+
+    fn function(&mut self) -> Result<Command, Error> {
+        let keyword = self.is_word("function");
+
+        if keyword {
+            self.position += 1;
+            self.skip_spaces();
+        }
+
+        let name = match self.peek() {
+            Some(Token::Value(name)) if is_valid_name(name) => name.clone(),
+            Some(token) if token.is_word_token() => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("`{}': not a valid identifier", token),
+                ))
+            }
+            _ => return Err(self.unexpected()),
+        };
+
+        self.position += 1;
+        self.skip_spaces();
+
+        // The parentheses are optional after `function`.
+        if !keyword || self.peek() == Some(&Token::OpenParen) {
+            for expected in [Token::OpenParen, Token::CloseParen] {
+                self.skip_spaces();
+
+                if self.peek() != Some(&expected) {
+                    return Err(self.unexpected());
+                }
+                self.position += 1;
+            }
+        }
+
+        self.skip_separators();
+
+        let Command::Compound(body, redirections) = self.command()? else {
+            return Err(self.unexpected());
+        };
+
+        Ok(Command::Function(Rc::new(Function {
+            name,
+            body,
+            redirections,
+        })))
+    }
+
+    fn simple(&mut self) -> Result<Command, Error> {
+        let start = self.position;
+
+        while let Some(token) = self.peek() {
+            if token.is_list_operator() || matches!(token, Token::Pipe | Token::CloseParen) {
+                break;
+            }
+
+            if token == &Token::OpenParen {
+                return Err(unexpected(token));
+            }
+
+            self.position += 1;
+        }
+
+        match trim_end(&self.tokens[start..self.position]) {
+            [] => Err(self.unexpected()),
+            tokens => Ok(Command::Simple(tokens.to_vec())),
+        }
+    }
+
+    /// The redirections after a compound command, which has to end right after them.
+    fn redirections(&mut self) -> Result<Vec<Token>, Error> {
+        let start = self.position;
+
+        loop {
+            self.skip_spaces();
+
+            match self.peek() {
+                Some(Token::HereDoc(_, _)) => self.position += 1,
+                Some(token) if token.is_redirection_token() => {
+                    self.position += 1;
+                    self.skip_spaces();
+
+                    if !self.peek().is_some_and(|t| t.is_word_token()) {
+                        return Err(self.unexpected());
+                    }
+
+                    while self.peek().is_some_and(|t| t.is_word_token()) {
+                        self.position += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        match self.peek() {
+            None => {}
+            Some(token)
+                if token.is_list_operator() || matches!(token, Token::Pipe | Token::CloseParen) => {
+            }
+            Some(token) => return Err(unexpected(token)),
+        }
+
+        Ok(trim_end(&self.tokens[start..self.position]).to_vec())
+    }
+
+    fn if_clause(&mut self) -> Result<Compound, Error> {
+        self.position += 1;
+
+        let mut branches = Vec::new();
+
+        loop {
+            let condition = self.body(&["then"])?;
+            self.keyword(&["then"])?;
+            let body = self.body(&["elif", "else", "fi"])?;
+
+            branches.push((condition, body));
+
+            match self.keyword(&["elif", "else", "fi"])? {
+                "elif" => continue,
+                "else" => {
+                    let otherwise = self.body(&["fi"])?;
+                    self.keyword(&["fi"])?;
+
+                    return Ok(Compound::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                    });
+                }
+                _ => {
+                    return Ok(Compound::If {
+                        branches,
+                        otherwise: None,
+                    })
+                }
+            }
+        }
+    }
+
+    fn loop_clause(&mut self, until: bool) -> Result<Compound, Error> {
+        self.position += 1;
+
+        let condition = self.body(&["do"])?;
+        self.keyword(&["do"])?;
+        let body = self.body(&["done"])?;
+        self.keyword(&["done"])?;
+
+        Ok(Compound::Loop {
+            until,
+            condition,
+            body,
+        })
+    }
+
+    fn for_clause(&mut self) -> Result<Compound, Error> {
+        self.position += 1;
+        self.skip_spaces();
+
+        let name = match self.peek() {
+            Some(Token::Value(name)) if is_valid_name(name) => name.clone(),
+            Some(token) if token.is_word_token() => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("`{}': not a valid identifier", token),
+                ))
+            }
+            _ => return Err(self.unexpected()),
+        };
+
+        self.position += 1;
+        self.skip_separators();
+
+        let mut words = None;
+
+        if self.is_word("in") {
+            self.position += 1;
+            let start = self.position;
+
+            while self
+                .peek()
+                .is_some_and(|t| t.is_word_token() || t == &Token::Space)
+            {
+                self.position += 1;
+            }
+
+            match self.peek() {
+                Some(Token::Semicolon) => {}
+                _ => return Err(self.unexpected()),
+            }
+
+            let list = &self.tokens[start..self.position];
+            let first = list.iter().position(|t| t != &Token::Space);
+            words = Some(trim_end(&list[first.unwrap_or(list.len())..]).to_vec());
+        }
+
+        self.keyword(&["do"])?;
+        let body = self.body(&["done"])?;
+        self.keyword(&["done"])?;
+
+        Ok(Compound::For { name, words, body })
+    }
+
+    fn case_clause(&mut self) -> Result<Compound, Error> {
+        self.position += 1;
+        self.skip_spaces();
+
+        let word = self.word()?;
+
+        self.skip_separators();
+
+        if !self.is_word("in") {
+            return Err(self.unexpected());
+        }
+        self.position += 1;
+
+        let mut arms = Vec::new();
+
+        loop {
+            self.skip_separators();
+
+            if self.is_word("esac") {
+                self.position += 1;
+                break;
+            }
+
+            if self.peek() == Some(&Token::OpenParen) {
+                self.position += 1;
+            }
+
+            let mut patterns = Vec::new();
+
+            loop {
+                self.skip_spaces();
+                patterns.push(self.word()?);
+                self.skip_spaces();
+
+                match self.peek() {
+                    Some(Token::Pipe) => self.position += 1,
+                    Some(Token::CloseParen) => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.unexpected()),
+                }
+            }
+
+            let body = self.list(&["esac"])?;
+
+            self.skip_separators();
+
+            match self.peek() {
+                Some(Token::DoubleSemicolon) => self.position += 1,
+                _ if self.is_word("esac") => {}
+                _ => return Err(self.unexpected()),
+            }
+
+            arms.push(CaseArm { patterns, body });
+        }
+
+        Ok(Compound::Case { word, arms })
+    }
+
+    /// The tokens of a single word.
+    fn word(&mut self) -> Result<Vec<Token>, Error> {
+        let start = self.position;
+
+        while self.peek().is_some_and(|t| t.is_word_token()) {
+            self.position += 1;
+        }
+
+        match start == self.position {
+            true => Err(self.unexpected()),
+            false => Ok(self.tokens[start..self.position].to_vec()),
+        }
+    }
+}
+
+fn trim_end(tokens: &[Token]) -> &[Token] {
+    let end = tokens
+        .iter()
+        .rposition(|t| t != &Token::Space)
+        .map_or(0, |end| end + 1);
+
+    &tokens[..end]
+}
+
+fn unexpected(token: &Token) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("syntax error near unexpected token `{}'", token),
+    )
+}
+
+fn incomplete() -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        "syntax error: unexpected end of file",
+    )
+}
@@ -1,27 +1,64 @@
 use crate::{
-    shell::core::{ShellCommand, ShellCommandProvider},
+    shell::{
+        core::{ShellCommand, ShellCommandProvider},
+        session::Session,
+    },
     tokenizer::Token,
 };
 
-use builtin::{cd::Cd, echo::Echo, exit::Exit, pwd::Pwd, type_::Type};
+use builtin::{
+    alias::Alias, bg::Bg, break_::Break, cd::Cd, complete::Complete, continue_::Continue,
+    disown::Disown, echo::Echo, env::Env, exit::Exit, export::Export, fg::Fg, jobs::Jobs,
+    local::Local, pwd::Pwd, read::Read, readonly::Readonly, return_::Return, set::Set,
+    shopt::Shopt, source::Source, type_::Type, unalias::Unalias, unset::Unset, wait::Wait,
+};
 
 pub mod builtin;
 
-pub const SUPPORTED_COMMANDS: [&str; 5] = ["echo", "type", "exit", "pwd", "cd"];
+pub const SUPPORTED_COMMANDS: [&str; 26] = [
+    "echo", "type", "exit", "pwd", "cd", "set", "export", "unset", "readonly", "env", "shopt",
+    "jobs", "fg", "bg", "wait", "disown", "break", "continue", "read", "local", "return", "alias",
+    "unalias", "source", ".", "complete",
+];
+
+/// The POSIX special builtins among them.
+pub const SPECIAL_COMMANDS: [&str; 9] = [
+    "exit", "set", "export", "unset", "readonly", "break", "continue", "return", ".",
+];
 
 pub struct CommandProvider {}
 
 impl ShellCommandProvider<Token> for CommandProvider {
-    fn run(cmd: &str, tokens: &[Token]) -> Result<String, std::io::Error> {
+    fn run(cmd: &str, tokens: &[Token], session: &mut Session) -> Result<String, std::io::Error> {
         match cmd {
-            "echo" => Echo::run(tokens),
-            "type" => Type::run(tokens),
-            "exit" => Exit::run(tokens),
-            "pwd" => Pwd::run(tokens),
-            "cd" => Cd::run(tokens),
+            "echo" => Echo::run(tokens, session),
+            "type" => Type::run(tokens, session),
+            "exit" => Exit::run(tokens, session),
+            "pwd" => Pwd::run(tokens, session),
+            "cd" => Cd::run(tokens, session),
+            "set" => Set::run(tokens, session),
+            "export" => Export::run(tokens, session),
+            "unset" => Unset::run(tokens, session),
+            "readonly" => Readonly::run(tokens, session),
+            "env" => Env::run(tokens, session),
+            "shopt" => Shopt::run(tokens, session),
+            "jobs" => Jobs::run(tokens, session),
+            "fg" => Fg::run(tokens, session),
+            "bg" => Bg::run(tokens, session),
+            "wait" => Wait::run(tokens, session),
+            "disown" => Disown::run(tokens, session),
+            "break" => Break::run(tokens, session),
+            "continue" => Continue::run(tokens, session),
+            "read" => Read::run(tokens, session),
+            "local" => Local::run(tokens, session),
+            "return" => Return::run(tokens, session),
+            "alias" => Alias::run(tokens, session),
+            "unalias" => Unalias::run(tokens, session),
+            "source" | "." => Source::run(tokens, session),
+            "complete" => Complete::run(tokens, session),
             _ => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
-                "shell: command not found",
+                format!("{}: command not found", cmd),
             )),
         }
     }
@@ -29,4 +66,8 @@ impl ShellCommandProvider<Token> for CommandProvider {
     fn get_commands() -> Vec<&'static str> {
         return SUPPORTED_COMMANDS.to_vec();
     }
+
+    fn is_special(cmd: &str) -> bool {
+        SPECIAL_COMMANDS.contains(&cmd)
+    }
 }
@@ -0,0 +1,79 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Alias {}
+
+impl ShellCommand<Token> for Alias {
+    /// `alias name=value` defines an alias, `alias name` shows one and `alias` shows them all.
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .filter(|arg| arg != "-p")
+            .collect();
+
+        if args.is_empty() {
+            let lines: Vec<String> = session
+                .aliases
+                .iter()
+                .map(|(name, value)| definition(name, value))
+                .collect();
+
+            return Ok(lines.join("\n"));
+        }
+
+        let mut lines = Vec::new();
+        let mut result = Ok(());
+
+        for arg in &args {
+            match arg.split_once('=') {
+                Some((name, _)) if !is_valid_alias(name) => {
+                    result = Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("alias: `{}': invalid alias name", name),
+                    ));
+                }
+                Some((name, value)) => {
+                    session.aliases.insert(name.to_string(), value.to_string());
+                }
+                None => match session.aliases.get(arg) {
+                    Some(value) => lines.push(definition(arg, value)),
+                    None => {
+                        result = Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("alias: {}: not found", arg),
+                        ))
+                    }
+                },
+            }
+        }
+
+        if let Err(err) = result {
+            if !lines.is_empty() {
+                session.write_stdout(format!("{}\n", lines.join("\n")).as_bytes())?;
+            }
+
+            return Err(err);
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// The alias the way `alias` defines it, which is how bash lists it.
+fn definition(name: &str, value: &str) -> String {
+    format!("alias {}='{}'", name, value.replace('\'', "'\\''"))
+}
+
+fn is_valid_alias(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|ch| ch.is_whitespace() || "/$`'\"\\|&;()<>".contains(ch))
+}
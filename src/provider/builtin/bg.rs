@@ -0,0 +1,45 @@
+use std::io::Error;
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Bg {}
+
+impl ShellCommand<Token> for Bg {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut specs: Vec<Option<String>> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| Some(t.serialize()))
+            .collect();
+
+        if specs.is_empty() {
+            specs.push(None);
+        }
+
+        let mut lines = Vec::new();
+        let mut result = Ok(());
+
+        for spec in specs {
+            let resumed = session.jobs.resolve(spec.as_deref()).and_then(|id| {
+                session.jobs.resume(id, false)?;
+                Ok(id)
+            });
+
+            match resumed {
+                Ok(id) => lines.extend(
+                    session
+                        .jobs
+                        .get(id)
+                        .map(|job| format!("[{}]+ {} &", job.id, job.command)),
+                ),
+                Err(err) => result = Err(Error::new(err.kind(), format!("bg: {}", err))),
+            }
+        }
+
+        result.map(|()| lines.join("\n"))
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{Flow, Session},
+    },
+    tokenizer::Token,
+};
+
+pub struct Break {}
+
+impl ShellCommand<Token> for Break {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let count = loop_count("break", tokens, session)?;
+        session.flow = Some(Flow::Break(count));
+
+        Ok(String::new())
+    }
+}
+
+/// The number of enclosing loops `break` or `continue` applies to, at most as many as there
+/// are.
+pub(super) fn loop_count(name: &str, tokens: &[Token], session: &Session) -> Result<usize, Error> {
+    if session.loop_depth == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{}: only meaningful in a `for', `while', or `until' loop",
+                name
+            ),
+        ));
+    }
+
+    let arg = tokens
+        .iter()
+        .skip(1)
+        .find(|t| !matches!(t, Token::Space))
+        .map(|t| t.serialize());
+
+    let count = match arg {
+        None => 1,
+        Some(arg) => match arg.parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{}: {}: loop count out of range", name, arg),
+                ))
+            }
+        },
+    };
+
+    Ok(count.min(session.loop_depth))
+}
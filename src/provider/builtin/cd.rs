@@ -3,11 +3,14 @@ use std::{
     io::{Error, ErrorKind},
 };
 
-use crate::{shell::core::ShellCommand, tokenizer::Token};
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
 pub struct Cd {}
 
 impl ShellCommand<Token> for Cd {
-    fn run(tokens: &[Token]) -> Result<String, Error> {
+    fn run(tokens: &[Token], _: &mut Session) -> Result<String, Error> {
         if tokens.len() < 3 {
             return Err(Error::new(ErrorKind::InvalidInput, "cd: missing argument"));
         }
@@ -0,0 +1,118 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{completion::Spec, core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+const USAGE: &str = "complete: usage: complete [-pr] [-W wordlist] [-F function] [name ...]";
+
+pub struct Complete {}
+
+impl ShellCommand<Token> for Complete {
+    /// `complete -W words name...` and `complete -F function name...` set how the arguments
+    /// of the commands are completed, `complete -r name...` takes it away and `complete -p`
+    /// lists the specs.
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut args = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .peekable();
+
+        let mut spec = Spec::default();
+        let mut remove = false;
+
+        while let Some(flag) = args.next_if(|arg| arg.starts_with('-') && arg.len() > 1) {
+            match flag.as_str() {
+                "-p" => {}
+                "-r" => remove = true,
+                "-W" | "-F" => {
+                    let Some(value) = args.next() else {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("complete: {}: option requires an argument\n{}", flag, USAGE),
+                        ));
+                    };
+
+                    match flag.as_str() {
+                        "-W" => spec.words = Some(value),
+                        _ => spec.function = Some(value),
+                    }
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("complete: {}: invalid option\n{}", flag, USAGE),
+                    ))
+                }
+            }
+        }
+
+        let names: Vec<String> = args.collect();
+
+        if spec != Spec::default() {
+            if names.is_empty() {
+                return Err(Error::new(ErrorKind::InvalidInput, USAGE));
+            }
+
+            for name in names {
+                session.completions.insert(name, spec.clone());
+            }
+
+            return Ok(String::new());
+        }
+
+        if remove && names.is_empty() {
+            session.completions.clear();
+            return Ok(String::new());
+        }
+
+        if names.is_empty() {
+            let lines: Vec<String> = session
+                .completions
+                .iter()
+                .map(|(name, spec)| definition(name, spec))
+                .collect();
+
+            return Ok(lines.join("\n"));
+        }
+
+        let mut lines = Vec::new();
+        let mut result = Ok(());
+
+        for name in &names {
+            let found = match remove {
+                true => session.completions.remove(name),
+                false => session.completions.get(name).cloned(),
+            };
+
+            match found {
+                Some(spec) if !remove => lines.push(definition(name, &spec)),
+                Some(_) => {}
+                None => {
+                    result = Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("complete: {}: no completion specification", name),
+                    ))
+                }
+            }
+        }
+
+        if let Err(err) = result {
+            if !lines.is_empty() {
+                session.write_stdout(format!("{}\n", lines.join("\n")).as_bytes())?;
+            }
+
+            return Err(err);
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// The spec the way `complete` defines it, which is how bash lists it.
+fn definition(name: &str, spec: &Spec) -> String {
+    format!("complete{} {}", spec, name)
+}
@@ -0,0 +1,22 @@
+use std::io::Error;
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{Flow, Session},
+    },
+    tokenizer::Token,
+};
+
+use super::break_::loop_count;
+
+pub struct Continue {}
+
+impl ShellCommand<Token> for Continue {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let count = loop_count("continue", tokens, session)?;
+        session.flow = Some(Flow::Continue(count));
+
+        Ok(String::new())
+    }
+}
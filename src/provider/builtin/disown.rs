@@ -0,0 +1,35 @@
+use std::io::Error;
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Disown {}
+
+impl ShellCommand<Token> for Disown {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        let ids = match args.as_slice() {
+            [flag] if flag == "-a" => Ok(session.jobs.ids()),
+            [] => session.jobs.resolve(None).map(|id| vec![id]),
+            specs => specs
+                .iter()
+                .map(|spec| session.jobs.resolve(Some(spec)))
+                .collect(),
+        }
+        .map_err(|err| Error::new(err.kind(), format!("disown: {}", err)))?;
+
+        for id in ids {
+            session.jobs.remove(id);
+        }
+
+        Ok(String::new())
+    }
+}
@@ -1,34 +1,40 @@
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 
-use crate::{shell::core::ShellCommand, tokenizer::Token};
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
 pub struct Echo {}
 
 impl ShellCommand<Token> for Echo {
-    fn run(tokens: &[Token]) -> Result<String, std::io::Error> {
-        if tokens.len() < 3 {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "No string input found.",
-            ));
-        }
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, std::io::Error> {
+        let mut args = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .peekable();
+
+        let mut newline = true;
 
-        let mut string = String::new();
-
-        for (i, token) in tokens.iter().skip(2).enumerate() {
-            match token {
-                Token::Space => {
-                    if i > 0 {
-                        string.push(' ');
-                    }
-                }
-                Token::Value(cmd) => string.push_str(cmd.as_str()),
-                Token::String(str, _) => string.push_str(str.as_str()),
-                Token::Appender(_) => unimplemented!(),
-                Token::Redirector(_) => unimplemented!(),
-                _ => {}
-            }
+        while args.next_if(|arg| arg == "-n").is_some() {
+            newline = false;
         }
 
-        return Ok(string);
+        let string = args.collect::<Vec<String>>().join(" ");
+
+        // The returned output always ends with a newline, an empty line or one without it is
+        // written directly.
+        match (string.is_empty(), newline) {
+            (false, true) => Ok(string),
+            (true, true) => write(session, "\n"),
+            (_, false) => write(session, &string),
+        }
     }
 }
+
+fn write(session: &Session, text: &str) -> Result<String, Error> {
+    session.write_stdout(text.as_bytes())?;
+
+    Ok(String::new())
+}
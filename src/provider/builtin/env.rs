@@ -0,0 +1,26 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Env {}
+
+impl ShellCommand<Token> for Env {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        if tokens.len() > 2 {
+            return Err(Error::new(ErrorKind::InvalidInput, "env: usage: env"));
+        }
+
+        let variables = &session.variables;
+
+        Ok(variables
+            .names()
+            .into_iter()
+            .filter(|name| variables.is_exported(name))
+            .filter_map(|name| Some(format!("{}={}", name, variables.get(name)?)))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
@@ -1,18 +1,32 @@
 use std::io::{Error, ErrorKind};
 
-use crate::{shell::core::ShellCommand, tokenizer::Token};
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
 
 pub struct Exit {}
 
 impl ShellCommand<Token> for Exit {
-    fn run(tokens: &[Token]) -> Result<String, Error> {
-        if tokens.get(2) == Some(&Token::Value("0".to_string())) {
-            return Err(Error::new(ErrorKind::Interrupted, ""));
-        } else {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                format!("{}: command not found", tokens[0]),
-            ));
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let arg = tokens
+            .iter()
+            .skip(1)
+            .find(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize());
+
+        // Without an argument the shell ends with the status of the last command.
+        if let Some(arg) = arg {
+            let Ok(status) = arg.parse::<i64>() else {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("exit: {}: numeric argument required", arg),
+                ));
+            };
+
+            session.last_status = (status & 0xff) as i32;
         }
+
+        return Err(Error::new(ErrorKind::Interrupted, ""));
     }
 }
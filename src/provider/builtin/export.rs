@@ -0,0 +1,76 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::Session,
+        variables::{is_valid_name, quote, split_assignment, Variables},
+    },
+    tokenizer::Token,
+};
+
+pub struct Export {}
+
+impl ShellCommand<Token> for Export {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        let exported = match args.first().map(|arg| arg.as_str()) {
+            Some("-p") if args.len() == 1 => return Ok(list_exported(&session.variables)),
+            Some("-n") => {
+                args.remove(0);
+                false
+            }
+            _ => true,
+        };
+
+        if args.is_empty() {
+            return Ok(list_exported(&session.variables));
+        }
+
+        let mut result = Ok(String::new());
+
+        for arg in &args {
+            let (name, value) = match split_assignment(arg) {
+                Some((name, value)) => (name, Some(value)),
+                None if is_valid_name(arg) => (arg.as_str(), None),
+                None => {
+                    result = Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("export: `{}': not a valid identifier", arg),
+                    ));
+                    continue;
+                }
+            };
+
+            if let Some(value) = value {
+                if let Err(err) = session.variables.set(name, value) {
+                    result = Err(err);
+                    continue;
+                }
+            }
+
+            session.variables.export(name, exported);
+        }
+
+        result
+    }
+}
+
+fn list_exported(variables: &Variables) -> String {
+    variables
+        .names()
+        .into_iter()
+        .filter(|name| variables.is_exported(name))
+        .map(|name| match variables.get(name) {
+            Some(value) => format!("declare -x {}={}", name, quote(value)),
+            None => format!("declare -x {}", name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,37 @@
+use std::io::{self, Error, Write};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+    util::error::status_result,
+};
+
+pub struct Fg {}
+
+impl ShellCommand<Token> for Fg {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let spec = tokens
+            .iter()
+            .skip(1)
+            .find(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize());
+
+        let id = session
+            .jobs
+            .resolve(spec.as_deref())
+            .map_err(|err| Error::new(err.kind(), format!("fg: {}", err)))?;
+
+        if let Some(job) = session.jobs.get(id) {
+            let mut stdout = io::stdout();
+            writeln!(stdout, "{}", job.command)?;
+            stdout.flush()?;
+        }
+
+        let status = session
+            .jobs
+            .resume(id, true)
+            .map_err(|err| Error::new(err.kind(), format!("fg: {}", err)))?;
+
+        status_result(status)
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, jobs::JobState, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Jobs {}
+
+impl ShellCommand<Token> for Jobs {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        let (pids_only, specs) = match args.split_first() {
+            Some((flag, specs)) if flag == "-p" => (true, specs),
+            Some((flag, _)) if flag.starts_with('-') => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "jobs: usage: jobs [-p] [jobspec ...]",
+                ))
+            }
+            _ => (false, args.as_slice()),
+        };
+
+        session.jobs.update()?;
+
+        let ids = match specs.is_empty() {
+            true => session.jobs.ids(),
+            false => specs
+                .iter()
+                .map(|spec| session.jobs.resolve(Some(spec)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| Error::new(err.kind(), format!("jobs: {}", err)))?,
+        };
+
+        let mut lines = Vec::new();
+
+        for id in ids {
+            let Some(job) = session.jobs.get(id) else {
+                continue;
+            };
+
+            let line = match pids_only {
+                true => job.pid().map(|pid| pid.to_string()),
+                false => session.jobs.format(id),
+            };
+            lines.extend(line);
+
+            // A finished job is reported once, like the notification before the prompt.
+            if matches!(job.state, JobState::Done(_)) {
+                session.jobs.remove(id);
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
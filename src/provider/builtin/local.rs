@@ -0,0 +1,57 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::Session,
+        variables::{is_valid_name, split_assignment},
+    },
+    tokenizer::Token,
+};
+
+pub struct Local {}
+
+impl ShellCommand<Token> for Local {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        if session.call_depth == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "local: can only be used in a function",
+            ));
+        }
+
+        let mut result = Ok(String::new());
+
+        for arg in &args {
+            let (name, value) = match split_assignment(arg) {
+                Some((name, value)) => (name, Some(value)),
+                None if is_valid_name(arg) => (arg.as_str(), None),
+                None => {
+                    result = Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("local: `{}': not a valid identifier", arg),
+                    ));
+                    continue;
+                }
+            };
+
+            let local = session.variables.make_local(name);
+
+            if let Err(err) = local.and_then(|()| match value {
+                Some(value) => session.variables.set(name, value),
+                None => Ok(()),
+            }) {
+                result = Err(err);
+            }
+        }
+
+        result
+    }
+}
@@ -1,5 +1,25 @@
+pub mod alias;
+pub mod bg;
+pub mod break_;
 pub mod cd;
+pub mod complete;
+pub mod continue_;
+pub mod disown;
 pub mod echo;
+pub mod env;
 pub mod exit;
+pub mod export;
+pub mod fg;
+pub mod jobs;
+pub mod local;
 pub mod pwd;
+pub mod read;
+pub mod readonly;
+pub mod return_;
+pub mod set;
+pub mod shopt;
+pub mod source;
 pub mod type_;
+pub mod unalias;
+pub mod unset;
+pub mod wait;
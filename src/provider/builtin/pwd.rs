@@ -1,9 +1,12 @@
-use crate::{shell::core::ShellCommand, tokenizer::Token};
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
 
 pub struct Pwd {}
 
 impl ShellCommand<Token> for Pwd {
-    fn run(_: &[Token]) -> Result<String, std::io::Error> {
+    fn run(_: &[Token], _: &mut Session) -> Result<String, std::io::Error> {
         match std::env::current_dir() {
             Ok(path) => Ok(path.to_str().unwrap().to_string()),
             Err(err) => Err(err),
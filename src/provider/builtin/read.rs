@@ -0,0 +1,145 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    os::fd::AsFd,
+};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{self, Session, Stream},
+        variables::is_valid_name,
+    },
+    tokenizer::Token,
+    util::error::status_result,
+};
+
+const DEFAULT_IFS: &str = " \t\n";
+
+pub struct Read {}
+
+impl ShellCommand<Token> for Read {
+    /// `read [-r] [name...]` reads a line into the names, split on `IFS` with the last name
+    /// taking the rest of the line. Fails at the end of the input.
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut raw = false;
+        let mut names = Vec::new();
+
+        for token in tokens.iter().skip(1) {
+            match token {
+                Token::Space => {}
+                Token::Argument(flag, false) if flag == "r" => raw = true,
+                Token::Argument(_, _) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("read: {}: invalid option", token.serialize()),
+                    ))
+                }
+                token => {
+                    let name = token.serialize();
+
+                    if !is_valid_name(&name) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("read: `{}': not a valid identifier", name),
+                        ));
+                    }
+                    names.push(name);
+                }
+            }
+        }
+
+        if names.is_empty() {
+            names.push(String::from("REPLY"));
+        }
+
+        let (line, complete) = match &session.streams[0] {
+            Stream::Fd(fd) => read_line(fd, raw)?,
+            Stream::Inherited => read_line(io::stdin(), raw)?,
+            Stream::Closed => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("read: read error: 0: {}", session::closed()),
+                ))
+            }
+        };
+
+        let ifs = session
+            .variables
+            .get("IFS")
+            .unwrap_or(DEFAULT_IFS)
+            .to_string();
+        let values = split(&line, &ifs, names.len());
+
+        for (index, name) in names.iter().enumerate() {
+            let value = values.get(index).map(String::as_str).unwrap_or_default();
+            session.variables.set(name, value)?;
+        }
+
+        status_result(match complete {
+            true => 0,
+            false => 1,
+        })
+    }
+}
+
+/// Reads up to a newline one byte at a time, so the input after it is left for the next
+/// command. Returns the line and whether it ended with a newline. Without `raw` a backslash
+/// keeps the next character and joins the lines around an escaped newline.
+fn read_line(fd: impl AsFd, raw: bool) -> Result<(String, bool), Error> {
+    let mut line = Vec::new();
+    let mut escaped = false;
+    let mut byte = [0; 1];
+
+    loop {
+        match rustix::io::read(&fd, &mut byte) {
+            Ok(0) => return Ok((String::from_utf8_lossy(&line).into_owned(), false)),
+            Ok(_) => {}
+            Err(rustix::io::Errno::INTR) => continue,
+            Err(err) => return Err(err.into()),
+        }
+
+        match byte[0] {
+            b'\n' if escaped => escaped = false,
+            b'\n' => break,
+            b'\\' if !raw && !escaped => escaped = true,
+            byte => {
+                escaped = false;
+                line.push(byte);
+            }
+        }
+    }
+
+    Ok((String::from_utf8_lossy(&line).into_owned(), true))
+}
+
+/// Splits the line into at most `count` fields. IFS whitespace around the line is dropped,
+/// the last field keeps the separators inside it.
+fn split(line: &str, ifs: &str, count: usize) -> Vec<String> {
+    let is_whitespace = |ch: char| ifs.contains(ch) && ch.is_whitespace();
+    let mut rest = line.trim_matches(is_whitespace);
+    let mut fields = Vec::new();
+
+    while fields.len() + 1 < count && !rest.is_empty() {
+        match rest.find(|ch: char| ifs.contains(ch)) {
+            Some(end) => {
+                fields.push(rest[..end].to_string());
+                rest = rest[end..].trim_start_matches(is_whitespace);
+
+                // A separator that isn't whitespace ends the field on its own.
+                if let Some(ch) = rest.chars().next().filter(|ch| ifs.contains(*ch)) {
+                    rest = rest[ch.len_utf8()..].trim_start_matches(is_whitespace);
+                }
+            }
+            None => {
+                fields.push(rest.to_string());
+                rest = "";
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        fields.push(rest.to_string());
+    }
+
+    fields
+}
@@ -0,0 +1,68 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::Session,
+        variables::{is_valid_name, quote, split_assignment, Variables},
+    },
+    tokenizer::Token,
+};
+
+pub struct Readonly {}
+
+impl ShellCommand<Token> for Readonly {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        match args.as_slice() {
+            [] => return Ok(list_readonly(&session.variables)),
+            [flag] if flag == "-p" => return Ok(list_readonly(&session.variables)),
+            _ => {}
+        }
+
+        let mut result = Ok(String::new());
+
+        for arg in &args {
+            let name = match split_assignment(arg) {
+                Some((name, value)) => match session.variables.set(name, value) {
+                    Ok(()) => name,
+                    Err(err) => {
+                        result = Err(err);
+                        continue;
+                    }
+                },
+                None if is_valid_name(arg) => arg.as_str(),
+                None => {
+                    result = Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("readonly: `{}': not a valid identifier", arg),
+                    ));
+                    continue;
+                }
+            };
+
+            session.variables.set_readonly(name);
+        }
+
+        result
+    }
+}
+
+fn list_readonly(variables: &Variables) -> String {
+    variables
+        .names()
+        .into_iter()
+        .filter(|name| variables.is_readonly(name))
+        .map(|name| match variables.get(name) {
+            Some(value) => format!("declare -r {}={}", name, quote(value)),
+            None => format!("declare -r {}", name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,47 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{Flow, Session},
+    },
+    tokenizer::Token,
+    util::error::status_result,
+};
+
+pub struct Return {}
+
+impl ShellCommand<Token> for Return {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        if session.call_depth == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "return: can only `return' from a function or sourced script",
+            ));
+        }
+
+        let arg = tokens
+            .iter()
+            .skip(1)
+            .find(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize());
+
+        // Without an argument the function returns the status of the last command.
+        let status = match arg {
+            None => session.last_status,
+            Some(arg) => match arg.parse::<i64>() {
+                Ok(status) => (status & 0xff) as i32,
+                Err(_) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("return: {}: numeric argument required", arg),
+                    ))
+                }
+            },
+        };
+
+        session.flow = Some(Flow::Return);
+
+        status_result(status)
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{Options, Session},
+        variables::{quote, Variables},
+    },
+    tokenizer::Token,
+};
+
+pub struct Set {}
+
+impl ShellCommand<Token> for Set {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        match args.as_slice() {
+            [] => Ok(list_variables(&session.variables)),
+            [flag] if flag == "-o" || flag == "+o" => Ok(list_options(&session.options)),
+            [flag, name] if flag == "-o" || flag == "+o" => {
+                if !Options::names().contains(&name.as_str()) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("set: {}: invalid option name", name),
+                    ));
+                }
+
+                session.options.set(name, flag == "-o");
+                Ok(String::new())
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "set: usage: set [-o option] [+o option]",
+            )),
+        }
+    }
+}
+
+fn list_options(options: &Options) -> String {
+    Options::names()
+        .iter()
+        .map(|name| {
+            let state = if options.get(name) == Some(true) {
+                "on"
+            } else {
+                "off"
+            };
+
+            format!("{:<15}\t{}", name, state)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn list_variables(variables: &Variables) -> String {
+    variables
+        .names()
+        .into_iter()
+        .filter_map(|name| Some(format!("{}={}", name, quote(variables.get(name)?))))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,72 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{
+        core::ShellCommand,
+        session::{Options, Session},
+    },
+    tokenizer::Token,
+};
+
+pub struct Shopt {}
+
+impl ShellCommand<Token> for Shopt {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        let (value, names) = match args.split_first() {
+            Some((flag, names)) if flag == "-s" => (Some(true), names),
+            Some((flag, names)) if flag == "-u" => (Some(false), names),
+            Some((flag, _)) if flag.starts_with('-') => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "shopt: usage: shopt [-s|-u] [optname ...]",
+                ))
+            }
+            _ => (None, args.as_slice()),
+        };
+
+        if let Some(name) = names
+            .iter()
+            .find(|name| !Options::shopt_names().contains(&name.as_str()))
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("shopt: {}: invalid shell option name", name),
+            ));
+        }
+
+        match value {
+            Some(value) => {
+                for name in names {
+                    session.options.set(name, value);
+                }
+
+                Ok(String::new())
+            }
+            None => Ok(list_options(&session.options, names)),
+        }
+    }
+}
+
+fn list_options(options: &Options, names: &[String]) -> String {
+    Options::shopt_names()
+        .into_iter()
+        .filter(|name| names.is_empty() || names.iter().any(|n| n == name))
+        .map(|name| {
+            let state = if options.get(name) == Some(true) {
+                "on"
+            } else {
+                "off"
+            };
+
+            format!("{:<15}\t{}", name, state)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,71 @@
+use std::{
+    env,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+use crate::{
+    interpreter::Interpreter,
+    provider::CommandProvider,
+    shell::{core::ShellCommand, script, session::Session},
+    tokenizer::{Token, Tokenizer},
+    util::error::status_result,
+};
+
+pub struct Source {}
+
+impl ShellCommand<Token> for Source {
+    /// `source file [args...]` runs the file in the current session, with the arguments as the
+    /// positional parameters while it runs.
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut args = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize());
+
+        let name = tokens.first().map(|t| t.serialize()).unwrap_or_default();
+
+        let Some(file) = args.next() else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{}: filename argument required", name),
+            ));
+        };
+
+        let path = find(&file);
+        let args: Vec<String> = args.collect();
+        let positional = match args.is_empty() {
+            true => None,
+            false => Some(std::mem::replace(&mut session.positional, args)),
+        };
+
+        let result =
+            script::source::<Token, Interpreter, Tokenizer, CommandProvider>(session, &path);
+
+        if let Some(positional) = positional {
+            session.positional = positional;
+        }
+
+        match result {
+            Ok(()) => status_result(session.last_status),
+            Err(err) if err.kind() == ErrorKind::Interrupted => Err(err),
+            // A missing file fails with 1 like any other error, 127 is for commands.
+            Err(err) => Err(Error::other(format!("{}: {}: {}", name, file, err))),
+        }
+    }
+}
+
+/// A name without a slash is looked up in `PATH` first, then in the current directory.
+fn find(file: &str) -> PathBuf {
+    if file.contains('/') {
+        return PathBuf::from(file);
+    }
+
+    env::var("PATH")
+        .unwrap_or_default()
+        .split(':')
+        .map(|dir| PathBuf::from(dir).join(file))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(file))
+}
@@ -1,59 +1,57 @@
 use std::io::{Error, ErrorKind};
 
 use crate::{
-    provider::SUPPORTED_COMMANDS, shell::core::ShellCommand, tokenizer::Token,
+    provider::SUPPORTED_COMMANDS,
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
     util::path::ExecutionPath,
 };
 
 pub struct Type {}
 
 impl ShellCommand<Token> for Type {
-    fn run(tokens: &[Token]) -> Result<String, Error> {
-        if tokens.len() < 3 {
+    /// Tells how every name would run, in the order the shell looks them up: aliases,
+    /// functions, builtins, then `PATH`.
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let names: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        if names.is_empty() {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
                 "This command needs argument.",
             ));
         }
 
-        for command in SUPPORTED_COMMANDS.iter() {
-            if tokens.get(2) == Some(&Token::Value(command.to_string())) {
-                return Ok(format!("{} is a shell builtin", command));
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+
+        for name in &names {
+            if let Some(value) = session.aliases.get(name) {
+                found.push(format!("{} is aliased to `{}`", name, value));
+            } else if let Some(function) = session.functions.get(name) {
+                found.push(format!("{} is a function\n{}", name, function));
+            } else if SUPPORTED_COMMANDS.contains(&name.as_str()) {
+                found.push(format!("{} is a shell builtin", name));
+            } else if let Some(path) = name.get_exec_path() {
+                found.push(format!("{} is {}", name, path.display()));
+            } else {
+                missing.push(format!("{} not found", name));
             }
         }
 
-        match tokens.get(2).unwrap() {
-            Token::Space => {
-                return Err(Error::new(
-                    ErrorKind::UnexpectedEof,
-                    "Third token shouldn't be a space. Fix this.",
-                ))
-            }
-            Token::Value(input) => match input.get_exec_path() {
-                Some(path) => return Ok(format!("{} is {}", input, path.to_str().unwrap())),
-                None => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("{} not found", input),
-                    ))
-                }
-            },
-            Token::Argument(_, _) => todo!(),
-            Token::String(input, _) => match input.get_exec_path() {
-                Some(path) => return Ok(format!("{} is {}", input, path.to_str().unwrap())),
-                None => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("{} not found", input),
-                    ))
-                }
-            },
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "bash: syntax error near unexpected token 'newline'",
-                ))
-            }
+        if missing.is_empty() {
+            return Ok(found.join("\n"));
         }
+
+        if !found.is_empty() {
+            session.write_stdout(format!("{}\n", found.join("\n")).as_bytes())?;
+        }
+
+        Err(Error::new(ErrorKind::InvalidInput, missing.join("\n")))
     }
 }
@@ -0,0 +1,44 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+};
+
+pub struct Unalias {}
+
+impl ShellCommand<Token> for Unalias {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        if args.first().is_some_and(|arg| arg == "-a") {
+            session.aliases.clear();
+            return Ok(String::new());
+        }
+
+        if args.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "unalias: usage: unalias [-a] name [name ...]",
+            ));
+        }
+
+        let mut result = Ok(String::new());
+
+        for name in &args {
+            if session.aliases.remove(name).is_none() {
+                result = Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("unalias: {}: not found", name),
+                ));
+            }
+        }
+
+        result
+    }
+}
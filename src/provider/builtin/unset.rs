@@ -0,0 +1,48 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session, variables::is_valid_name},
+    tokenizer::Token,
+};
+
+pub struct Unset {}
+
+impl ShellCommand<Token> for Unset {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let mut args = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .peekable();
+
+        let mut functions = false;
+
+        while let Some(flag) = args.next_if(|arg| arg == "-v" || arg == "-f") {
+            functions = flag == "-f";
+        }
+
+        let mut result = Ok(String::new());
+
+        for name in args {
+            if functions {
+                session.functions.remove(&name);
+                continue;
+            }
+
+            if !is_valid_name(&name) {
+                result = Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("unset: `{}': not a valid identifier", name),
+                ));
+                continue;
+            }
+
+            if let Err(err) = session.variables.unset(&name) {
+                result = Err(Error::new(err.kind(), format!("unset: {}", err)));
+            }
+        }
+
+        result
+    }
+}
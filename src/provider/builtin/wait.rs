@@ -0,0 +1,51 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    shell::{core::ShellCommand, session::Session},
+    tokenizer::Token,
+    util::error::status_result,
+};
+
+pub struct Wait {}
+
+impl ShellCommand<Token> for Wait {
+    fn run(tokens: &[Token], session: &mut Session) -> Result<String, Error> {
+        let args: Vec<String> = tokens
+            .iter()
+            .skip(1)
+            .filter(|t| !matches!(t, Token::Space))
+            .map(|t| t.serialize())
+            .collect();
+
+        // Without arguments every job is waited for and the status is always 0.
+        if args.is_empty() {
+            for id in session.jobs.ids() {
+                session.jobs.wait(id)?;
+            }
+
+            return Ok(String::new());
+        }
+
+        let mut status = 0;
+
+        for arg in &args {
+            let id = match arg.parse::<i32>() {
+                Ok(pid) => session.jobs.find_pid(pid).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::NotFound,
+                        format!("wait: pid {} is not a child of this shell", pid),
+                    )
+                }),
+                Err(_) => session
+                    .jobs
+                    .resolve(Some(arg))
+                    .map_err(|err| Error::new(ErrorKind::NotFound, format!("wait: {}", err))),
+            };
+
+            // An unknown job fails with `NotFound`, which makes the status 127.
+            status = session.jobs.wait(id?)?;
+        }
+
+        status_result(status)
+    }
+}
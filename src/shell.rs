@@ -1,35 +1,116 @@
-use std::io::{self, Error, ErrorKind, Stderr, Stdout, Write};
+use std::{
+    io::{self, Error, ErrorKind, Stderr, Stdout, Write},
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, Arc},
+};
+
+use crate::util::unicode;
 
+use completion::Completion;
 use core::{ShellCommandProvider, ShellInterpreter, ShellTokenizer};
 use crossterm::{
-    cursor::{self, MoveRight, MoveToColumn},
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    cursor::{MoveDown, MoveTo, MoveToColumn, MoveUp},
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
-    style::Print,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    style::{Print, Stylize},
+    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
 };
+use editor::Editor;
+use history::History;
+use paste::{expand_tabs, Paste};
+use prompt::Prompt;
+use search::ReverseSearch;
+use session::Session;
+use signal_hook::consts::{SIGINT, SIGQUIT};
+use vi::{Mode, Outcome, Vi};
 
-const PREFIX: &str = "$ ";
-
+pub mod args;
+pub mod completion;
 pub mod core;
+pub mod editor;
+pub mod history;
+pub mod jobs;
+pub mod paste;
+pub mod prompt;
+pub mod script;
+pub mod search;
+pub mod session;
+pub mod subshell;
+pub mod variables;
+pub mod vi;
 
 pub struct Shell {
-    buffer: String,
+    line: Editor,
+    /// State of the vi editing mode, used while `set -o vi` is on.
+    vi: Vi,
     stdout: Stdout,
     stderr: Stderr,
-    // history: Vec<String>,
+    history: History,
+    search: Option<ReverseSearch>,
+    /// The prompt the line being edited starts with.
+    prompt: Prompt,
+    /// `PS2`, before each line after the first of a command that goes on over several lines.
+    continuation: Prompt,
+    /// How many rows below the first row of the input the terminal cursor is, where a redraw
+    /// starts from. Lines wider than the terminal count every row they wrap onto.
+    cursor_row: usize,
+    /// How many rows below the first row of the input its last one is.
+    last_row: usize,
+    /// What was pasted into the line, and whether to ask before running it.
+    paste: Paste,
+    /// Whether the last key was Tab, so another one lists the candidates.
+    after_tab: bool,
+    /// Whether the last key was Esc, which makes the next one count as typed with Alt.
+    meta: bool,
+    session: Session,
+    /// Files sourced before the first prompt, like `~/.shellrc`.
+    startup_files: Vec<PathBuf>,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Shell {
     pub fn new() -> Self {
+        Self::with_session(Session::new())
+    }
+
+    pub fn with_session(session: Session) -> Self {
         Self {
-            buffer: String::new(),
+            line: Editor::new(),
+            vi: Vi::new(),
             stdout: io::stdout(),
             stderr: io::stderr(),
-            // history: Vec::new(),
+            // Read in `init`, once the startup files could set `HISTFILE`.
+            history: History::new(None, 0, 0),
+            search: None,
+            prompt: Prompt::default(),
+            continuation: Prompt::plain("> "),
+            cursor_row: 0,
+            last_row: 0,
+            paste: Paste::new(),
+            after_tab: false,
+            meta: false,
+            session,
+            startup_files: Vec::new(),
         }
     }
 
+    pub fn with_startup_files(mut self, files: Vec<PathBuf>) -> Self {
+        self.startup_files = files;
+        self
+    }
+
+    /// Status of the last command, what the shell exits with.
+    pub fn last_status(&self) -> i32 {
+        self.session.last_status
+    }
+
     pub async fn run<
         T,
         SI: ShellInterpreter<T>,
@@ -38,7 +119,7 @@ impl Shell {
     >(
         &mut self,
     ) -> Result<(), Error> {
-        self.init()?;
+        self.init::<T, SI, ST, SCC>()?;
 
         loop {
             self.stdout.flush()?;
@@ -64,29 +145,123 @@ impl Shell {
         &mut self,
     ) -> Result<(), Error> {
         match event::read()? {
-            Event::FocusGained => todo!(),
-            Event::FocusLost => todo!(),
+            Event::FocusGained | Event::FocusLost | Event::Mouse(_) => Ok(()),
             Event::Key(key_event) => {
-                self.handle_modifiers(key_event)?;
+                // The highlight of pasted text goes away with the next key.
+                if self.paste.clear_highlight() {
+                    self.redraw_line()?;
+                }
+
+                if self.paste.is_confirming() {
+                    return self.handle_confirm_keys::<T, SI, ST, SCC>(key_event);
+                }
+
+                let key_event = match std::mem::take(&mut self.meta) {
+                    true => KeyEvent::new(key_event.code, key_event.modifiers | KeyModifiers::ALT),
+                    false => key_event,
+                };
+
+                if self.handle_modifiers(key_event)? {
+                    return Ok(());
+                }
+
+                if self.search.is_some() && self.handle_search_keys(key_event)? {
+                    return Ok(());
+                }
+
+                if self.session.options.vi {
+                    match self.vi.handle(key_event, &mut self.line, &mut self.history) {
+                        Outcome::Handled => {
+                            self.after_tab = false;
+                            return self.redraw_line();
+                        }
+                        Outcome::Bell => return self.stdout.write_all(&[7]),
+                        Outcome::Unhandled => {}
+                    }
+                }
+
+                if self.handle_editing_keys(key_event)? {
+                    return Ok(());
+                }
+
                 self.handle_keys::<T, SI, ST, SCC>(key_event)?;
 
                 Ok(())
             }
-            Event::Mouse(_) => todo!(),
-            Event::Paste(_) => todo!(),
-            Event::Resize(_, _) => todo!(),
+            Event::Paste(text) if !self.paste.is_confirming() => {
+                if self.search.is_some() {
+                    self.handle_search_keys(KeyEvent::from(KeyCode::Null))?;
+                }
+
+                self.paste.insert(&mut self.line, &text);
+                self.after_tab = false;
+
+                self.redraw_line()
+            }
+            Event::Paste(_) => Ok(()),
+            // The line wraps at another column now.
+            Event::Resize(_, _) if self.paste.is_confirming() => Ok(()),
+            Event::Resize(_, _) => match self.search.is_some() {
+                true => self.redraw_search(),
+                false => self.redraw_line(),
+            },
         }
     }
 
-    fn init(&mut self) -> Result<(), Error> {
+    fn init<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+        &mut self,
+    ) -> Result<(), Error> {
+        // Ctrl-C and Ctrl-\ should only reach the foreground command, never the shell itself.
+        signal_hook::flag::register(SIGINT, Arc::clone(&self.session.interrupted))?;
+        signal_hook::flag::register(SIGQUIT, Arc::clone(&self.session.interrupted))?;
+        self.session.jobs.enable_control();
+
+        for file in std::mem::take(&mut self.startup_files) {
+            self.source_startup_file::<T, SI, ST, SCC>(&file)?;
+        }
+
+        // Completion specs come after the startup files, which may choose their directory.
+        for file in completion::spec_files(&self.session) {
+            self.source_startup_file::<T, SI, ST, SCC>(&file)?;
+        }
+
+        // The history settings too can come from the startup files.
+        self.history = History::from_variables(&self.session.variables);
+        self.history.load()?;
+
         enable_raw_mode()?;
+        execute!(self.stdout, EnableBracketedPaste)?;
 
-        execute!(self.stdout, Print("$ "),)?;
-        Ok(())
+        self.show_prompt::<T, SI, SCC>()
+    }
+
+    /// A broken startup file is reported and the shell starts anyway, unless it ran `exit`.
+    fn source_startup_file<
+        T,
+        SI: ShellInterpreter<T>,
+        ST: ShellTokenizer<T>,
+        SCC: ShellCommandProvider<T>,
+    >(
+        &mut self,
+        file: &Path,
+    ) -> Result<(), Error> {
+        match script::source::<T, SI, ST, SCC>(&mut self.session, file) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::Interrupted => Err(err),
+            Err(err) => writeln!(
+                self.stderr,
+                "{}: {}: {}",
+                self.session.name,
+                file.display(),
+                err
+            ),
+        }
     }
 
     fn uninit(&mut self) -> Result<(), Error> {
+        execute!(self.stdout, DisableBracketedPaste)?;
         disable_raw_mode()?;
+        self.history.save()?;
 
         execute!(self.stdout)?;
         Ok(())
@@ -101,121 +276,520 @@ impl Shell {
         &mut self,
         key_event: KeyEvent,
     ) -> Result<(), Error> {
-        let KeyEvent { code, .. } = key_event;
+        let KeyEvent {
+            code, modifiers, ..
+        } = key_event;
+        let repeated_tab = std::mem::replace(&mut self.after_tab, code == KeyCode::Tab);
 
         match code {
+            // A key with Ctrl or Alt that nothing is bound to isn't text.
+            KeyCode::Char(_) if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                self.stdout.write_all(&[7])?
+            }
+            // Drawn again whole, since the character can wrap the line onto a row of its own.
             KeyCode::Char(ch) => {
-                let relative_cursor_x = cursor::position()?.0 as usize - PREFIX.len();
+                self.line.insert(ch);
+                self.redraw_line()?;
+            }
+            KeyCode::Enter => {
+                let tokens = Tokenizer::tokenize(self.line.text().trim())
+                    .and_then(|tokens| Interpreter::check(&tokens).map(|()| tokens));
 
-                if relative_cursor_x < self.buffer.len() {
-                    self.buffer.insert(relative_cursor_x, ch);
+                // An open quote, a trailing `|` or an `if` without its `fi` go on on a new line
+                // of the same input, which stays editable as a whole.
+                if matches!(&tokens, Err(err) if err.kind() == ErrorKind::UnexpectedEof) {
+                    self.line.move_to(self.line.text().len());
+                    self.line.insert('\n');
 
-                    execute!(
-                        self.stdout,
-                        Clear(ClearType::CurrentLine),
-                        MoveToColumn(0),
-                        Print(PREFIX),
-                        Print(&self.buffer),
-                        MoveToColumn((relative_cursor_x + 3) as u16)
-                    )?;
-                } else {
-                    self.buffer.push(ch);
-                    execute!(self.stdout, Print(ch))?;
+                    return self.redraw_line();
                 }
-            }
-            KeyCode::Enter => {
-                if !self.buffer.is_empty() {
-                    execute!(self.stdout, Print("\r\n"))?;
 
-                    let tokens = Tokenizer::tokenize(self.buffer.trim())?;
-                    match Interpreter::run::<CommandProvider>(&tokens) {
-                        Ok(ok) => {
-                            self.stdout.write(&ok)?;
-                        }
-                        Err(err) => {
-                            if err.kind() == ErrorKind::Interrupted {
-                                return Err(err);
-                            }
-                            self.stderr.write(&err.to_string().as_bytes())?;
-                        }
-                    }
+                if let Some(question) = self
+                    .paste
+                    .confirm(&self.line, self.session.options.confirmpaste)
+                {
+                    self.leave_input()?;
+                    return execute!(self.stdout, Print(question));
                 }
-                execute!(self.stdout, Print("\r\n"), Print(PREFIX))?;
 
-                self.buffer.clear();
+                self.leave_input()?;
+                self.submit::<T, Interpreter, CommandProvider>(tokens)?;
             }
             KeyCode::Tab => {
-                match CommandProvider::get_commands()
-                    .iter()
-                    .find(|c| c.starts_with(self.buffer.trim()))
-                {
-                    Some(found_command) => {
-                        let rest_of_the_command = &found_command[self.buffer.trim().len()..];
+                let cursor = self.line.cursor();
+                let completion = Completion::new::<T, Interpreter, Tokenizer, CommandProvider>(
+                    self.line.text(),
+                    cursor,
+                    &mut self.session,
+                );
 
-                        self.stdout.write(rest_of_the_command.as_bytes())?;
-                        self.buffer.push_str(rest_of_the_command);
+                match completion.replacement() {
+                    Some(replacement) => {
+                        self.line.replace(completion.start()..cursor, &replacement);
+                        self.redraw_line()?;
+                    }
+                    None if repeated_tab && completion.candidates().len() > 1 => {
+                        let width = terminal_columns();
+
+                        self.leave_input()?;
+                        for line in completion.columns(width) {
+                            execute!(self.stdout, Print(line), Print("\r\n"))?;
+                        }
+                        self.redraw_line()?;
                     }
                     None => {
-                        self.stdout.write(&[7])?;
+                        self.stdout.write_all(&[7])?;
                     }
                 }
             }
             KeyCode::Backspace => {
-                self.buffer.pop();
-
-                execute!(
-                    self.stdout,
-                    Clear(ClearType::CurrentLine),
-                    MoveToColumn(0),
-                    Print(PREFIX),
-                    Print(&self.buffer)
-                )?;
+                self.line.delete_backward();
+                self.redraw_line()?;
+            }
+            KeyCode::Delete => {
+                self.line.delete_forward();
+                self.redraw_line()?;
             }
             KeyCode::Left => {
-                execute!(self.stdout, cursor::MoveLeft(1))?;
+                self.line.move_left();
+                self.redraw_line()?;
             }
             KeyCode::Right => {
-                let relative_cursor_x = cursor::position()?.0 as usize - PREFIX.len();
-
-                if relative_cursor_x < self.buffer.len() {
-                    execute!(self.stdout, MoveRight(1))?;
-                }
-            }
-            KeyCode::Up => todo!(),
-            KeyCode::Down => todo!(),
-            KeyCode::Home => todo!(),
-            KeyCode::End => todo!(),
-            KeyCode::PageUp => todo!(),
-            KeyCode::PageDown => todo!(),
-            KeyCode::BackTab => todo!(),
-            KeyCode::Delete => todo!(),
-            KeyCode::Insert => todo!(),
-            KeyCode::F(_) => todo!(),
-            KeyCode::Null => todo!(),
-            KeyCode::Esc => todo!(),
-            KeyCode::CapsLock => todo!(),
-            KeyCode::ScrollLock => todo!(),
-            KeyCode::NumLock => todo!(),
-            KeyCode::PrintScreen => todo!(),
-            KeyCode::Pause => todo!(),
-            KeyCode::Menu => todo!(),
-            KeyCode::KeypadBegin => todo!(),
-            KeyCode::Media(_) => todo!(),
-            KeyCode::Modifier(_) => todo!(),
+                self.line.move_right();
+                self.redraw_line()?;
+            }
+            KeyCode::Home => {
+                self.line.move_home();
+                self.redraw_line()?;
+            }
+            KeyCode::End => {
+                self.line.move_end();
+                self.redraw_line()?;
+            }
+            KeyCode::Up => {
+                if self.line.move_up() {
+                    self.redraw_line()?;
+                } else if let Some(entry) = self.history.older(self.line.text()) {
+                    self.line.set(entry);
+                    self.redraw_line()?;
+                }
+            }
+            KeyCode::Down => {
+                if self.line.move_down() {
+                    self.redraw_line()?;
+                } else if let Some(entry) = self.history.newer() {
+                    self.line.set(entry);
+                    self.redraw_line()?;
+                }
+            }
+            // Esc followed by a key is the same as the key with Alt, for terminals without a
+            // Meta key.
+            KeyCode::Esc => self.meta = true,
+            KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::BackTab
+            | KeyCode::Insert
+            | KeyCode::F(_) => self.stdout.write_all(&[7])?,
+            KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::ScrollLock
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+            | KeyCode::Media(_)
+            | KeyCode::Modifier(_) => {}
         }
 
         Ok(())
     }
 
-    fn handle_modifiers(&self, key_event: KeyEvent) -> Result<(), Error> {
+    /// Runs the input, which the cursor has already left, and shows a new prompt.
+    fn submit<T, SI: ShellInterpreter<T>, SCC: ShellCommandProvider<T>>(
+        &mut self,
+        tokens: Result<Vec<T>, Error>,
+    ) -> Result<(), Error> {
+        let input = self.line.take();
+        self.vi.reset();
+        self.history.push(&input);
+        self.paste.reset();
+
+        if !input.trim().is_empty() {
+            // Commands print their own output, so the terminal goes back to cooked mode meanwhile.
+            execute!(self.stdout, DisableBracketedPaste)?;
+            disable_raw_mode()?;
+
+            let result = tokens.and_then(|tokens| SI::run::<SCC>(&tokens, &mut self.session));
+
+            enable_raw_mode()?;
+            execute!(self.stdout, EnableBracketedPaste)?;
+
+            // A job in its own process group gets the Ctrl-C instead of the shell.
+            let interrupted = self.session.interrupted.swap(false, Ordering::Relaxed)
+                || self.session.last_status == 128 + SIGINT;
+
+            if interrupted {
+                execute!(self.stdout, Print("\r\n"))?;
+            }
+
+            if let Err(err) = result {
+                if err.kind() == ErrorKind::Interrupted {
+                    return Err(err);
+                }
+                if !err.to_string().is_empty() {
+                    write!(self.stderr, "{}\r\n", err)?;
+                }
+            }
+        }
+
+        for line in self.session.jobs.notifications() {
+            write!(self.stderr, "{}\r\n", line)?;
+        }
+        self.show_prompt::<T, SI, SCC>()
+    }
+
+    /// The answer to whether to run a pasted command: `y` runs it, any other key goes back
+    /// to editing it.
+    fn handle_confirm_keys<
+        T,
+        SI: ShellInterpreter<T>,
+        ST: ShellTokenizer<T>,
+        SCC: ShellCommandProvider<T>,
+    >(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> Result<(), Error> {
+        if self.paste.answer(key_event) {
+            execute!(self.stdout, Print("y\r\n"))?;
+
+            let tokens = ST::tokenize(self.line.text().trim())
+                .and_then(|tokens| SI::check(&tokens).map(|()| tokens));
+
+            return self.submit::<T, SI, SCC>(tokens);
+        }
+
+        execute!(self.stdout, Print("\r\n"))?;
+        self.redraw_line()
+    }
+
+    /// Prints `PS1`, and gets `PS2` ready for the lines after the first.
+    fn show_prompt<T, SI: ShellInterpreter<T>, SCC: ShellCommandProvider<T>>(
+        &mut self,
+    ) -> Result<(), Error> {
+        let variables = &self.session.variables;
+        let primary = variables.get("PS1").unwrap_or("$ ").to_string();
+        let secondary = variables.get("PS2").unwrap_or("> ").to_string();
+
+        self.prompt = Prompt::new::<T, SI, SCC>(&primary, &mut self.session);
+        self.continuation = Prompt::new::<T, SI, SCC>(&secondary, &mut self.session);
+
+        // The terminal is in raw mode, where a line feed doesn't go back to the first column.
+        execute!(self.stdout, Print(self.prompt.head().replace('\n', "\r\n")))?;
+
+        self.redraw_line()
+    }
+
+    /// Shows the vi mode before the prompt, like bash with `show-mode-in-prompt`.
+    fn mode_indicator(&self) -> &'static str {
+        match (self.session.options.vi, self.vi.mode()) {
+            (false, _) => "",
+            (true, Mode::Insert) => "(ins)",
+            (true, Mode::Normal) => "(cmd)",
+        }
+    }
+
+    /// Prints the input again after the prompt, each line after the first after `PS2`, and
+    /// puts the cursor back where the editor has it.
+    fn redraw_line(&mut self) -> Result<(), Error> {
+        self.clear_input()?;
+
+        // A vi `/` search is typed in place of the line.
+        if let Some(query) = self.vi.query() {
+            return execute!(self.stdout, Print(format!("/{}", query)));
+        }
+
+        let indicator = self.mode_indicator();
+        let text = self.line.text();
+        let pasted = self.paste.highlight().unwrap_or_default();
+        // Each line of pasted text is highlighted by itself, leaving `PS2` as it is.
+        let highlighted: Vec<String> = text[pasted.clone()]
+            .split('\n')
+            .map(|line| match line.is_empty() {
+                true => String::new(),
+                false => expand_tabs(line).reverse().to_string(),
+            })
+            .collect();
+
+        let shown = format!(
+            "{}{}{}",
+            expand_tabs(&text[..pasted.start]),
+            highlighted.join("\n"),
+            expand_tabs(&text[pasted.end..])
+        );
+
+        // Each line with its prompt, and the columns they take.
+        let prompt = |row: usize| match row {
+            0 => (
+                format!("{}{}", indicator, self.prompt.last_line()),
+                indicator.len() + self.prompt.width(),
+            ),
+            _ => (
+                self.continuation.last_line().to_string(),
+                self.continuation.width(),
+            ),
+        };
+        let lines: Vec<(String, usize)> = shown
+            .split('\n')
+            .zip(expand_tabs(text).split('\n'))
+            .enumerate()
+            .map(|(row, (shown, plain))| {
+                let (prompt, width) = prompt(row);
+                (prompt + shown, width + unicode::width(plain))
+            })
+            .collect();
+
+        let columns = terminal_columns();
+        let before = expand_tabs(self.line.before_cursor());
+        let row = before.matches('\n').count();
+        let current = before.rsplit('\n').next().unwrap_or_default();
+        let column = prompt(row).1 + unicode::width(current);
+
+        execute!(self.stdout, Print(wrap(&lines, columns)))?;
+
+        self.cursor_row = wrapped_rows(&lines[..row], columns) + column / columns;
+        self.last_row = wrapped_rows(&lines, columns) - 1;
+
+        if self.last_row > self.cursor_row {
+            execute!(
+                self.stdout,
+                MoveUp((self.last_row - self.cursor_row) as u16)
+            )?;
+        }
+
+        execute!(self.stdout, MoveToColumn((column % columns) as u16))
+    }
+
+    /// Goes back to the first line of the input and clears it and everything below.
+    fn clear_input(&mut self) -> Result<(), Error> {
+        if self.cursor_row > 0 {
+            execute!(self.stdout, MoveUp(self.cursor_row as u16))?;
+        }
+        self.cursor_row = 0;
+        self.last_row = 0;
+
+        execute!(
+            self.stdout,
+            MoveToColumn(0),
+            Clear(ClearType::FromCursorDown)
+        )
+    }
+
+    /// Moves below the last line of the input, for what gets printed after it.
+    fn leave_input(&mut self) -> Result<(), Error> {
+        let rows = self.last_row.saturating_sub(self.cursor_row);
+
+        if rows > 0 {
+            execute!(self.stdout, MoveDown(rows as u16))?;
+        }
+        self.cursor_row = 0;
+        self.last_row = 0;
+
+        execute!(self.stdout, Print("\r\n"))
+    }
+
+    /// Ctrl-L, clears the screen and shows the prompt and the line at the top.
+    fn clear_screen(&mut self) -> Result<(), Error> {
+        execute!(
+            self.stdout,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print(self.prompt.head().replace('\n', "\r\n"))
+        )?;
+        self.cursor_row = 0;
+        self.last_row = 0;
+
+        self.redraw_line()
+    }
+
+    /// Emacs editing commands bound to Ctrl and Alt combinations. Returns `true` when the key
+    /// was one of them.
+    fn handle_editing_keys(&mut self, key_event: KeyEvent) -> Result<bool, Error> {
         let KeyEvent {
             code, modifiers, ..
         } = key_event;
 
-        if modifiers.contains(KeyModifiers::CONTROL) && matches!(code, KeyCode::Char('c')) {
-            return Err(io::Error::new(ErrorKind::Interrupted, "ctrl-c"));
+        let KeyCode::Char(ch) = code else {
+            return Ok(false);
+        };
+
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            match ch {
+                'a' => self.line.move_home(),
+                'e' => self.line.move_end(),
+                'b' => self.line.move_left(),
+                'f' => self.line.move_right(),
+                'd' => self.line.delete_forward(),
+                'w' => self.line.kill_word_backward(),
+                'k' => self.line.kill_to_end(),
+                'u' => self.line.kill_to_start(),
+                'y' => self.line.yank(),
+                't' => self.line.transpose(),
+                'l' => {
+                    self.after_tab = false;
+                    self.clear_screen()?;
+
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            }
+        } else if modifiers.contains(KeyModifiers::ALT) {
+            match ch {
+                'b' => self.line.move_word_left(),
+                'f' => self.line.move_word_right(),
+                'd' => self.line.kill_word_forward(),
+                'y' => self.line.yank_pop(),
+                _ => return Ok(false),
+            }
+        } else {
+            return Ok(false);
+        }
+
+        self.after_tab = false;
+        self.redraw_line()?;
+
+        Ok(true)
+    }
+
+    /// Returns `true` when the key was consumed by the search and shouldn't reach `handle_keys`.
+    fn handle_search_keys(&mut self, key_event: KeyEvent) -> Result<bool, Error> {
+        let Some(search) = self.search.as_mut() else {
+            return Ok(false);
+        };
+
+        let plain = !key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        match key_event.code {
+            KeyCode::Char(ch) if plain => search.push(ch, &self.history),
+            KeyCode::Backspace => search.pop(&self.history),
+            KeyCode::Esc => {
+                self.line.set(search.original());
+                self.search = None;
+                self.redraw_line()?;
+
+                return Ok(true);
+            }
+            _ => {
+                // Any other key accepts the match and is then handled as usual.
+                if let Some(matched) = search.matched(&self.history) {
+                    self.line.set(matched);
+                }
+                self.search = None;
+                self.redraw_line()?;
+
+                return Ok(false);
+            }
         }
 
+        self.redraw_search()?;
+
+        Ok(true)
+    }
+
+    fn redraw_search(&mut self) -> Result<(), Error> {
+        let Some(search) = &self.search else {
+            return Ok(());
+        };
+
+        let label = if search.is_failed() {
+            "(failed reverse-i-search)"
+        } else {
+            "(reverse-i-search)"
+        };
+        let text = format!(
+            "{}`{}': {}",
+            label,
+            search.query(),
+            search.matched(&self.history).unwrap_or("")
+        );
+
+        let lines: Vec<(String, usize)> = text
+            .split('\n')
+            .map(|line| (line.to_string(), unicode::width(line)))
+            .collect();
+        let columns = terminal_columns();
+
+        self.clear_input()?;
+        execute!(self.stdout, Print(wrap(&lines, columns)))?;
+        // A match of several lines leaves the cursor on its last one.
+        self.cursor_row = wrapped_rows(&lines, columns) - 1;
+        self.last_row = self.cursor_row;
+
         Ok(())
     }
+
+    /// Returns `true` when the key combination was fully handled here.
+    fn handle_modifiers(&mut self, key_event: KeyEvent) -> Result<bool, Error> {
+        let KeyEvent {
+            code, modifiers, ..
+        } = key_event;
+
+        if !modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(false);
+        }
+
+        match code {
+            KeyCode::Char('c') => Err(io::Error::new(ErrorKind::Interrupted, "ctrl-c")),
+            // The end of the input on an empty line, like `exit`.
+            KeyCode::Char('d') if self.line.text().is_empty() && self.search.is_none() => {
+                self.leave_input()?;
+
+                Err(io::Error::new(ErrorKind::Interrupted, ""))
+            }
+            KeyCode::Char('r') => {
+                match self.search.as_mut() {
+                    Some(search) => search.older(&self.history),
+                    None => self.search = Some(ReverseSearch::new(self.line.text())),
+                }
+
+                self.redraw_search()?;
+
+                Ok(true)
+            }
+            KeyCode::Char('g') if self.search.is_some() => {
+                self.handle_search_keys(KeyEvent::from(KeyCode::Esc))
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Columns of the terminal, 80 when it can't tell.
+fn terminal_columns() -> usize {
+    match terminal::size() {
+        Ok((width, _)) if width > 0 => width as usize,
+        _ => 80,
+    }
+}
+
+/// Lines to print in raw mode, given with the columns they take. A line that ends at the
+/// right edge leaves the cursor there until the next character, so a space takes it to the
+/// row below, which the line takes too.
+fn wrap(lines: &[(String, usize)], columns: usize) -> String {
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|(line, width)| match *width > 0 && width % columns == 0 {
+            true => format!("{} ", line),
+            false => line.clone(),
+        })
+        .collect();
+
+    lines.join("\r\n")
+}
+
+/// Rows the lines printed by `wrap` take on the screen.
+fn wrapped_rows(lines: &[(String, usize)], columns: usize) -> usize {
+    lines.iter().map(|(_, width)| width / columns + 1).sum()
 }
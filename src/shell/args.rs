@@ -0,0 +1,156 @@
+use std::{
+    env,
+    io::{self, Error, ErrorKind, IsTerminal},
+    path::PathBuf,
+};
+
+/// Where the shell reads its commands from.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// The line editor on a terminal.
+    Interactive,
+    /// The string after `-c`.
+    Command(String),
+    Script(PathBuf),
+    /// Stdin that isn't a terminal, or `-s`.
+    Stdin,
+}
+
+/// The command line the shell was started with.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Arguments {
+    pub mode: Mode,
+    /// `$0`, the shell itself or the script.
+    pub name: String,
+    pub positional: Vec<String>,
+    /// `-l` or `--login`, which also reads the login profile.
+    pub login: bool,
+    /// `--norc` skips the startup file.
+    pub norc: bool,
+    /// `--rcfile FILE` replaces the startup file.
+    pub rcfile: Option<PathBuf>,
+}
+
+impl Arguments {
+    /// Parses `shell [-s] [args...]`, `shell -c command [name [args...]]` and
+    /// `shell script [args...]`, each with the startup options. Without a command or script,
+    /// stdin decides whether the shell is interactive.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Error> {
+        let mut args = args.into_iter();
+        let mut name = args.next().unwrap_or_else(|| String::from("shell"));
+        let mut command = false;
+        let mut stdin = false;
+        let mut login = name.starts_with('-');
+        let mut norc = false;
+        let mut rcfile = None;
+        let mut rest = Vec::new();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-c" => command = true,
+                "-s" => stdin = true,
+                "-l" | "--login" => login = true,
+                "--norc" => norc = true,
+                "--rcfile" => match args.next() {
+                    Some(file) => rcfile = Some(PathBuf::from(file)),
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            "--rcfile: option requires an argument",
+                        ))
+                    }
+                },
+                "--" | "-" => break,
+                flag if flag.starts_with('-') => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("{}: invalid option", flag),
+                    ))
+                }
+                _ => {
+                    rest.push(arg);
+                    break;
+                }
+            }
+        }
+
+        rest.extend(args);
+
+        if command {
+            if rest.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "-c: option requires an argument",
+                ));
+            }
+
+            let command = rest.remove(0);
+
+            if !rest.is_empty() {
+                name = rest.remove(0);
+            }
+
+            return Ok(Self {
+                mode: Mode::Command(command),
+                name,
+                positional: rest,
+                login,
+                norc,
+                rcfile,
+            });
+        }
+
+        let mode = match (stdin, rest.is_empty()) {
+            (false, false) => {
+                name = rest.remove(0);
+                Mode::Script(PathBuf::from(&name))
+            }
+            _ if io::stdin().is_terminal() => Mode::Interactive,
+            _ => Mode::Stdin,
+        };
+
+        Ok(Self {
+            mode,
+            name,
+            positional: rest,
+            login,
+            norc,
+            rcfile,
+        })
+    }
+
+    /// The files an interactive shell runs before its first prompt: the login profile, then
+    /// the file from `--rcfile`, `$ENV` or `~/.shellrc`. Default files that don't exist are
+    /// left out, a missing file that was asked for is reported when it's read.
+    pub fn startup_files(&self) -> Vec<PathBuf> {
+        let home = env::var("HOME").map(PathBuf::from).ok();
+        let mut files = Vec::new();
+
+        if self.login {
+            files.extend(home.as_ref().map(|home| home.join(".shell_profile")));
+        }
+
+        if !self.norc {
+            match (&self.rcfile, env::var("ENV")) {
+                (Some(rcfile), _) => files.push(rcfile.clone()),
+                (None, Ok(rcfile)) if !rcfile.is_empty() => files.push(expand_home(&rcfile)),
+                _ => files.extend(home.map(|home| home.join(".shellrc"))),
+            }
+        }
+
+        let explicit = self.rcfile.is_some();
+
+        files
+            .into_iter()
+            .filter(|file| file.exists() || explicit && Some(file) == self.rcfile.as_ref())
+            .collect()
+    }
+}
+
+/// Replaces a leading `~` with the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::BTreeSet,
+    env,
+    fmt::Display,
+    fs::{self, File},
+    io::{Error, Read, Write},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use rustix::pipe::PipeFlags;
+
+use crate::util::unicode;
+
+use super::{
+    core::{ShellCommandProvider, ShellInterpreter, ShellTokenizer},
+    session::{Session, Stream},
+    subshell,
+    variables::quote,
+};
+
+/// Words after which a new command starts, like `if` in `if true`.
+const COMMAND_PREFIXES: [&str; 10] = [
+    "if", "then", "else", "elif", "while", "until", "do", "!", "{", "time",
+];
+
+/// Characters that need a backslash to stay part of a completed word.
+const SPECIAL: &str = " \t\n\"'\\$`&|;()<>*?[]!{}#";
+
+/// How `complete` completes the arguments of a command.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Spec {
+    /// `-W`: a list of words.
+    pub words: Option<String>,
+    /// `-F`: a function that sets `COMPREPLY`.
+    pub function: Option<String>,
+}
+
+impl Display for Spec {
+    /// The options `complete` takes to define the spec again.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(words) = &self.words {
+            write!(f, " -W {}", quote(words))?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, " -F {}", function)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Candidate {
+    text: String,
+    is_dir: bool,
+}
+
+/// The word before the cursor and everything it can be completed to.
+pub struct Completion {
+    start: usize,
+    word: String,
+    /// The words of the command before the completed one.
+    words: Vec<String>,
+    /// The quote left open in the word, like `"` in `cat "my fi`.
+    quote: Option<char>,
+    /// Whether the word names a command rather than an argument.
+    command: bool,
+    /// Whether the candidates come from a `complete` spec.
+    programmed: bool,
+    candidates: Vec<Candidate>,
+}
+
+impl Completion {
+    /// Completes the word ending at byte offset `cursor` of `line`: command names in command
+    /// position, the spec set with `complete` for arguments of a command that has one, and
+    /// files and directories everywhere else.
+    pub fn new<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+        line: &str,
+        cursor: usize,
+        session: &mut Session,
+    ) -> Self {
+        let mut completion = Self::scan(&line[..cursor]);
+        let spec = match completion.command {
+            true => None,
+            false => completion
+                .words
+                .first()
+                .and_then(|name| session.completions.get(name))
+                .cloned(),
+        };
+
+        completion.programmed = spec.is_some();
+        completion.candidates = match spec {
+            Some(spec) => completion.programmed::<T, SI, ST, SCC>(&spec, line, session),
+            None if completion.is_path() => files(&completion, session),
+            None => commands(&completion.word, session, &SCC::get_commands()),
+        };
+        completion.candidates.sort_by(|a, b| a.text.cmp(&b.text));
+        completion.candidates.dedup_by(|a, b| a.text == b.text);
+
+        completion
+    }
+
+    /// Byte offset where the completed word starts.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn candidates(&self) -> Vec<&str> {
+        self.candidates.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    /// The quoted text that replaces the word: the only candidate, followed by a space unless
+    /// it is a directory, or the longest common prefix of all of them. `None` when it wouldn't
+    /// add anything to the word.
+    pub fn replacement(&self) -> Option<String> {
+        match self.candidates.as_slice() {
+            [] => None,
+            [only] if only.is_dir => Some(self.quoted(&only.text)),
+            [only] => {
+                let closing = self.quote.map(String::from).unwrap_or_default();
+                Some(format!("{}{} ", self.quoted(&only.text), closing))
+            }
+            [first, rest @ ..] => {
+                let prefix = rest.iter().fold(first.text.as_str(), |prefix, candidate| {
+                    common_prefix(prefix, &candidate.text)
+                });
+
+                (prefix.len() > self.word.len()).then(|| self.quoted(prefix))
+            }
+        }
+    }
+
+    /// The candidates in columns that fit in `width`, sorted down each column like `ls`.
+    pub fn columns(&self, width: usize) -> Vec<String> {
+        let names: Vec<String> = self.candidates.iter().map(|c| self.name(c)).collect();
+        let column_width = names
+            .iter()
+            .map(|name| unicode::width(name))
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let columns = (width / column_width).max(1);
+        let rows = names.len().div_ceil(columns);
+
+        (0..rows)
+            .map(|row| {
+                let line: String = names
+                    .iter()
+                    .skip(row)
+                    .step_by(rows)
+                    .map(|name| {
+                        let padding = column_width - unicode::width(name);
+                        format!("{}{}", name, " ".repeat(padding))
+                    })
+                    .collect();
+
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Paths are completed for arguments and for commands like `./run`.
+    fn is_path(&self) -> bool {
+        !self.programmed && (!self.command || self.word.contains('/'))
+    }
+
+    /// The words of the spec that start with the word, then every line the function puts in
+    /// `COMPREPLY`. The function is called with the command name, the word and the word
+    /// before it, and finds all the words of the command in `COMP_WORDS`, one per line, with
+    /// the index of the completed one in `COMP_CWORD`.
+    fn programmed<
+        T,
+        SI: ShellInterpreter<T>,
+        ST: ShellTokenizer<T>,
+        SCC: ShellCommandProvider<T>,
+    >(
+        &self,
+        spec: &Spec,
+        line: &str,
+        session: &mut Session,
+    ) -> Vec<Candidate> {
+        let mut names: Vec<String> = spec
+            .words
+            .iter()
+            .flat_map(|words| words.split_whitespace())
+            .filter(|name| name.starts_with(&self.word))
+            .map(String::from)
+            .collect();
+
+        if let Some(function) = &spec.function {
+            let words: Vec<&str> = self
+                .words
+                .iter()
+                .chain([&self.word])
+                .map(String::as_str)
+                .collect();
+            let index = words.len() - 1;
+            let input = format!(
+                "{} {} {} {}",
+                function,
+                quote(words[0]),
+                quote(&self.word),
+                quote(words[index - 1])
+            );
+
+            let variables = [
+                ("COMP_WORDS", words.join("\n")),
+                ("COMP_CWORD", index.to_string()),
+                ("COMP_LINE", line.to_string()),
+            ];
+            let reply = run_function::<T, SI, ST, SCC>(&input, variables, session);
+
+            names.extend(reply.unwrap_or_default());
+        }
+
+        names
+            .into_iter()
+            .map(|text| Candidate {
+                text,
+                is_dir: false,
+            })
+            .collect()
+    }
+
+    fn name(&self, candidate: &Candidate) -> String {
+        if !self.is_path() {
+            return candidate.text.clone();
+        }
+
+        let text = candidate.text.trim_end_matches('/');
+        let name = text.rsplit('/').next().unwrap_or(text);
+
+        match candidate.is_dir {
+            true => format!("{}/", name),
+            false => name.to_string(),
+        }
+    }
+
+    fn quoted(&self, text: &str) -> String {
+        if let Some(quote) = self.quote {
+            return format!("{}{}", quote, text);
+        }
+
+        let (home, rest) = match text.strip_prefix('~') {
+            Some(rest) => ("~", rest),
+            None => ("", text),
+        };
+
+        rest.chars().fold(home.to_string(), |mut quoted, ch| {
+            if SPECIAL.contains(ch) {
+                quoted.push('\\');
+            }
+            quoted.push(ch);
+            quoted
+        })
+    }
+
+    /// Finds where the last word of `line` starts, removes its quoting and tells whether it
+    /// names a command.
+    fn scan(line: &str) -> Self {
+        let mut start = 0;
+        let mut word = String::new();
+        let mut words = Vec::new();
+        let mut quote = None;
+        let mut escaped = false;
+        let mut command_position = true;
+
+        for (index, ch) in line.char_indices() {
+            match (quote, ch) {
+                _ if escaped => {
+                    escaped = false;
+                    word.push(ch);
+                }
+                (Some(open), ch) if ch == open => quote = None,
+                (Some(_), ch) => word.push(ch),
+                (None, '\\') => escaped = true,
+                (None, '\'' | '"') => quote = Some(ch),
+                (None, ch) if ch.is_whitespace() || "|;&()<>".contains(ch) => {
+                    if "|;&(\n".contains(ch) {
+                        command_position = true;
+                        words.clear();
+                    } else if !word.is_empty() {
+                        command_position = command_position
+                            && (COMMAND_PREFIXES.contains(&word.as_str()) || is_assignment(&word));
+
+                        match command_position {
+                            true => words.clear(),
+                            false => words.push(std::mem::take(&mut word)),
+                        }
+                    }
+
+                    start = index + ch.len_utf8();
+                    word.clear();
+                }
+                (None, ch) => word.push(ch),
+            }
+        }
+
+        Self {
+            start,
+            word,
+            words,
+            command: command_position,
+            programmed: false,
+            quote,
+            candidates: Vec::new(),
+        }
+    }
+}
+
+/// Builtins, functions, aliases and executables on `PATH` starting with `prefix`.
+fn commands(prefix: &str, session: &Session, builtins: &[&str]) -> Vec<Candidate> {
+    let path = session.variables.get("PATH").unwrap_or_default();
+    let executables = path
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .flat_map(|dir| fs::read_dir(dir).into_iter().flatten().flatten())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+
+            (name.starts_with(prefix) && is_executable(&entry.path())).then_some(name)
+        });
+
+    let names: BTreeSet<String> = builtins
+        .iter()
+        .map(|name| name.to_string())
+        .chain(session.functions.keys().cloned())
+        .chain(session.aliases.keys().cloned())
+        .filter(|name| name.starts_with(prefix))
+        .chain(executables)
+        .collect();
+
+    names
+        .into_iter()
+        .map(|text| Candidate {
+            text,
+            is_dir: false,
+        })
+        .collect()
+}
+
+/// Runs a completion function with its `variables` in a subshell, so it can't change the
+/// shell, with its input and output on `/dev/null` so it can't touch the line being edited.
+/// Returns the lines of the `COMPREPLY` it sets.
+fn run_function<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+    input: &str,
+    variables: [(&str, String); 3],
+    session: &mut Session,
+) -> Result<Vec<String>, Error> {
+    let tokens = ST::tokenize(input)?;
+    let null = || -> Result<Stream, Error> {
+        let file = File::options().read(true).write(true).open("/dev/null")?;
+        Ok(Stream::Fd(file.into()))
+    };
+    let streams = [null()?, null()?, null()?];
+    let (reader, writer) = rustix::pipe::pipe_with(PipeFlags::CLOEXEC)?;
+
+    let pid = subshell::fork(session, |session| {
+        session.streams = streams;
+
+        for (name, value) in variables {
+            let _ = session.variables.set(name, &value);
+        }
+        let _ = session.variables.unset("COMPREPLY");
+        let _ = SI::run::<SCC>(&tokens, session);
+
+        let reply = session.variables.get("COMPREPLY").unwrap_or_default();
+
+        match File::from(writer).write_all(reply.as_bytes()) {
+            Ok(()) => 0,
+            Err(_) => 1,
+        }
+    });
+
+    let mut reply = String::new();
+    let read = File::from(reader).read_to_string(&mut reply);
+    subshell::wait(pid?)?;
+    read?;
+
+    Ok(reply
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Files and directories starting with the word, which may begin with `~/` unless quoted.
+/// Hidden entries only match when the last component starts with a `.`, and a command only
+/// matches directories and executables.
+fn files(completion: &Completion, session: &Session) -> Vec<Candidate> {
+    let word = completion.word.as_str();
+    let tilde = completion.quote.is_none();
+
+    if tilde && word == "~" {
+        return vec![Candidate {
+            text: String::from("~/"),
+            is_dir: true,
+        }];
+    }
+
+    let (dir, prefix) = match word.rfind('/') {
+        Some(index) => word.split_at(index + 1),
+        None => ("", word),
+    };
+    let listed = match dir.strip_prefix("~/").filter(|_| tilde) {
+        Some(rest) => format!("{}/{}", home(session), rest),
+        None if dir.is_empty() => String::from("."),
+        None => dir.to_string(),
+    };
+
+    fs::read_dir(listed)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+
+            let path = entry.path();
+            let is_dir = path.is_dir();
+
+            if completion.command && !is_dir && !is_executable(&path) {
+                return None;
+            }
+
+            Some(Candidate {
+                text: match is_dir {
+                    true => format!("{}{}/", dir, name),
+                    false => format!("{}{}", dir, name),
+                },
+                is_dir,
+            })
+        })
+        .collect()
+}
+
+/// Completion spec files sourced at startup: every file in `$SHELL_COMPLETION_DIR`, or in
+/// `~/.shell_completion.d`, in order of name.
+pub fn spec_files(session: &Session) -> Vec<PathBuf> {
+    let dir = match session.variables.get("SHELL_COMPLETION_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(home(session)).join(".shell_completion.d"),
+    };
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    files
+}
+
+fn home(session: &Session) -> String {
+    match session.variables.get("HOME") {
+        Some(home) => home.trim_end_matches('/').to_string(),
+        None => env::var("HOME").unwrap_or_default(),
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+fn is_assignment(word: &str) -> bool {
+    super::variables::split_assignment(word).is_some()
+}
+
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let end = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map(|((index, _), _)| index)
+        .unwrap_or_else(|| a.len().min(b.len()));
+
+    &a[..end]
+}
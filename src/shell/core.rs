@@ -1,7 +1,18 @@
 use std::io::Error;
 
+use super::session::Session;
+
 pub trait ShellInterpreter<T> {
-    fn run<R: ShellCommandProvider<T>>(tokens: &[T]) -> Result<Vec<u8>, Error>;
+    fn run<R: ShellCommandProvider<T>>(tokens: &[T], session: &mut Session) -> Result<i32, Error>;
+    /// Checks the syntax without running anything. Input that continues on the next line
+    /// fails with `UnexpectedEof`.
+    fn check(tokens: &[T]) -> Result<(), Error>;
+    /// Expands the parameters and command substitutions of `text`, the way the body of a
+    /// here-document is expanded.
+    fn expand<R: ShellCommandProvider<T>>(
+        text: &str,
+        session: &mut Session,
+    ) -> Result<String, Error>;
 }
 
 pub trait ShellTokenizer<T> {
@@ -9,10 +20,12 @@ pub trait ShellTokenizer<T> {
 }
 
 pub trait ShellCommandProvider<T> {
-    fn run(cmd: &str, tokens: &[T]) -> Result<String, std::io::Error>;
+    fn run(cmd: &str, tokens: &[T], session: &mut Session) -> Result<String, std::io::Error>;
     fn get_commands() -> Vec<&'static str>;
+    /// Whether `cmd` is a POSIX special builtin, after which its prefix assignments stay set.
+    fn is_special(cmd: &str) -> bool;
 }
 
 pub trait ShellCommand<T> {
-    fn run(tokens: &[T]) -> Result<String, std::io::Error>;
+    fn run(tokens: &[T], session: &mut Session) -> Result<String, std::io::Error>;
 }
@@ -0,0 +1,315 @@
+use std::ops::Range;
+
+use crate::util::unicode;
+
+/// Killed texts kept for yanking back, like in readline.
+const KILL_RING_SIZE: usize = 16;
+
+/// The line being typed, with the cursor as a byte offset into it, always on a grapheme
+/// boundary. Implements the Emacs editing commands and their kill ring.
+#[derive(Default)]
+pub struct Editor {
+    text: String,
+    cursor: usize,
+    /// Killed texts, the newest last.
+    kill_ring: Vec<String>,
+    last: Last,
+}
+
+/// What the previous command was, since consecutive kills go into the same kill ring entry
+/// and `yank_pop` only follows a yank.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+enum Last {
+    #[default]
+    Other,
+    Kill,
+    /// Where the yanked text is in the line, and which kill ring entry it is.
+    Yank(Range<usize>, usize),
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The text before the cursor, whose width tells where the cursor is on the screen.
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replaces the whole line, like when going through the history, with the cursor at its
+    /// end.
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+        self.last = Last::Other;
+    }
+
+    /// Takes the line out, leaving an empty one.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        self.last = Last::Other;
+        std::mem::take(&mut self.text)
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        self.text.insert(self.cursor, ch);
+        self.cursor += ch.len_utf8();
+        self.last = Last::Other;
+    }
+
+    /// Replaces part of the line, leaving the cursor after the new text.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.cursor = range.start + text.len();
+        self.text.replace_range(range, text);
+        self.last = Last::Other;
+    }
+
+    /// Backspace.
+    pub fn delete_backward(&mut self) {
+        let start = self.previous(self.cursor);
+        self.replace(start..self.cursor, "");
+    }
+
+    /// Delete, the character under the cursor.
+    pub fn delete_forward(&mut self) {
+        let end = self.next(self.cursor);
+        self.text.replace_range(self.cursor..end, "");
+        self.last = Last::Other;
+    }
+
+    pub fn move_left(&mut self) {
+        self.move_to(self.previous(self.cursor));
+    }
+
+    pub fn move_right(&mut self) {
+        self.move_to(self.next(self.cursor));
+    }
+
+    /// To the start of the line the cursor is on, in a command of several lines.
+    pub fn move_home(&mut self) {
+        self.move_to(self.line_start(self.cursor));
+    }
+
+    /// To the end of the line the cursor is on, in a command of several lines.
+    pub fn move_end(&mut self) {
+        self.move_to(self.line_end(self.cursor));
+    }
+
+    /// Up in a command of several lines, to the same column on the line above. `false` on
+    /// the first line, where Up goes back in the history instead.
+    pub fn move_up(&mut self) -> bool {
+        let start = self.line_start(self.cursor);
+
+        if start == 0 {
+            return false;
+        }
+
+        let column = unicode::width(&self.text[start..self.cursor]);
+        self.move_to_column(self.line_start(start - 1), column);
+
+        true
+    }
+
+    /// Down to the same column on the line below, `false` on the last line.
+    pub fn move_down(&mut self) -> bool {
+        let Some(end) = self.text[self.cursor..].find('\n') else {
+            return false;
+        };
+
+        let start = self.line_start(self.cursor);
+        let column = unicode::width(&self.text[start..self.cursor]);
+        self.move_to_column(self.cursor + end + 1, column);
+
+        true
+    }
+
+    /// Alt-B, to the start of the word before the cursor.
+    pub fn move_word_left(&mut self) {
+        self.move_to(self.word_start());
+    }
+
+    /// Alt-F, to the end of the word after the cursor.
+    pub fn move_word_right(&mut self) {
+        self.move_to(self.word_end());
+    }
+
+    /// Ctrl-W, the whitespace-delimited word before the cursor.
+    pub fn kill_word_backward(&mut self) {
+        let before = &self.text[..self.cursor];
+        let start = before
+            .trim_end_matches(char::is_whitespace)
+            .trim_end_matches(|ch: char| !ch.is_whitespace())
+            .len();
+
+        self.kill(start..self.cursor);
+    }
+
+    /// Alt-D, up to the end of the word after the cursor.
+    pub fn kill_word_forward(&mut self) {
+        self.kill(self.cursor..self.word_end());
+    }
+
+    /// Ctrl-K, up to the end of the line.
+    pub fn kill_to_end(&mut self) {
+        // At the end of a line it joins the next one.
+        let end = match self.line_end(self.cursor) {
+            end if end == self.cursor && end < self.text.len() => end + 1,
+            end => end,
+        };
+
+        self.kill(self.cursor..end);
+    }
+
+    /// Ctrl-U, up to the start of the line.
+    pub fn kill_to_start(&mut self) {
+        self.kill(self.line_start(self.cursor)..self.cursor);
+    }
+
+    /// Ctrl-Y, inserts the last killed text.
+    pub fn yank(&mut self) {
+        if let Some(index) = self.kill_ring.len().checked_sub(1) {
+            self.yank_entry(self.cursor..self.cursor, index);
+        }
+    }
+
+    /// Alt-Y, right after a yank, replaces the yanked text with the entry killed before it.
+    pub fn yank_pop(&mut self) {
+        if let Last::Yank(range, index) = self.last.clone() {
+            let index = index.checked_sub(1).unwrap_or(self.kill_ring.len() - 1);
+            self.yank_entry(range, index);
+        }
+    }
+
+    /// Ctrl-T, swaps the characters around the cursor and moves past them. At the end of the
+    /// line, swaps the last two.
+    pub fn transpose(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+
+        let at = match self.cursor == self.text.len() {
+            true => self.previous(self.cursor),
+            false => self.cursor,
+        };
+        let start = self.previous(at);
+        let end = self.next(at);
+
+        if start == at || at == end {
+            return;
+        }
+
+        let swapped = format!("{}{}", &self.text[at..end], &self.text[start..at]);
+        self.replace(start..end, &swapped);
+    }
+
+    /// Moves the cursor to a byte offset, which has to be on a grapheme boundary.
+    pub fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor;
+        self.last = Last::Other;
+    }
+
+    /// Where the line that `index` is on starts.
+    fn line_start(&self, index: usize) -> usize {
+        self.text[..index]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1)
+    }
+
+    /// Where the line that `index` is on ends, before its newline.
+    fn line_end(&self, index: usize) -> usize {
+        self.text[index..]
+            .find('\n')
+            .map_or(self.text.len(), |newline| index + newline)
+    }
+
+    /// Moves along the line starting at `start` as far as `column`, or to its end when it's
+    /// shorter.
+    fn move_to_column(&mut self, start: usize, column: usize) {
+        let mut index = start;
+        let mut width = 0;
+
+        while index < self.text.len() && !self.text[index..].starts_with('\n') {
+            let next = self.next(index);
+            width += unicode::width(&self.text[index..next]);
+
+            if width > column {
+                break;
+            }
+            index = next;
+        }
+
+        self.move_to(index);
+    }
+
+    /// Removes the text into the kill ring. Kills one after another make a single entry, in
+    /// the order the text had in the line.
+    fn kill(&mut self, range: Range<usize>) {
+        let killed: String = self.text.drain(range.clone()).collect();
+        let forward = range.start == self.cursor;
+        self.cursor = range.start;
+
+        match (self.last == Last::Kill, self.kill_ring.last_mut()) {
+            (true, Some(entry)) if forward => entry.push_str(&killed),
+            (true, Some(entry)) => entry.insert_str(0, &killed),
+            _ if killed.is_empty() => {}
+            _ => {
+                self.kill_ring.push(killed);
+
+                if self.kill_ring.len() > KILL_RING_SIZE {
+                    self.kill_ring.remove(0);
+                }
+            }
+        }
+
+        self.last = Last::Kill;
+    }
+
+    fn yank_entry(&mut self, range: Range<usize>, index: usize) {
+        let entry = self.kill_ring[index].clone();
+        let start = range.start;
+
+        self.replace(range, &entry);
+        self.last = Last::Yank(start..start + entry.len(), index);
+    }
+
+    /// Where the word before the cursor starts, words being made of letters and digits.
+    fn word_start(&self) -> usize {
+        let before = &self.text[..self.cursor];
+
+        before
+            .trim_end_matches(|ch: char| !ch.is_alphanumeric())
+            .trim_end_matches(char::is_alphanumeric)
+            .len()
+    }
+
+    /// Where the word after the cursor ends.
+    fn word_end(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let rest = after
+            .trim_start_matches(|ch: char| !ch.is_alphanumeric())
+            .trim_start_matches(char::is_alphanumeric);
+
+        self.text.len() - rest.len()
+    }
+
+    fn previous(&self, index: usize) -> usize {
+        unicode::previous_boundary(&self.text, index)
+    }
+
+    fn next(&self, index: usize) -> usize {
+        unicode::next_boundary(&self.text, index)
+    }
+}
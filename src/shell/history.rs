@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+use super::variables::Variables;
+
+const DEFAULT_HISTFILE: &str = ".shell_history";
+const DEFAULT_HISTSIZE: usize = 500;
+
+pub struct History {
+    entries: Vec<String>,
+    position: usize,
+    draft: Option<String>,
+    path: Option<PathBuf>,
+    size: usize,
+    file_size: usize,
+}
+
+impl History {
+    pub fn new(path: Option<PathBuf>, size: usize, file_size: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            position: 0,
+            draft: None,
+            path,
+            size,
+            file_size,
+        }
+    }
+
+    /// Reads the shell variables `HISTFILE`, `HISTSIZE` and `HISTFILESIZE` the same way bash
+    /// does.
+    pub fn from_variables(variables: &Variables) -> Self {
+        let path = match variables.get("HISTFILE") {
+            Some("") => None,
+            Some(path) => Some(PathBuf::from(path)),
+            None => variables
+                .get("HOME")
+                .map(|home| PathBuf::from(home).join(DEFAULT_HISTFILE)),
+        };
+
+        let size = read_limit(variables, "HISTSIZE").unwrap_or(DEFAULT_HISTSIZE);
+        let file_size = read_limit(variables, "HISTFILESIZE").unwrap_or(size);
+
+        Self::new(path, size, file_size)
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.as_str())
+    }
+
+    /// Finds the most recent entry before `before` that contains `query`.
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        let end = before.min(self.entries.len());
+
+        self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+
+    pub fn load(&mut self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        self.entries = Vec::new();
+        let mut entry = String::new();
+
+        for line in contents.lines() {
+            // A line ending with a backslash goes on in the next one, see `save`.
+            match line.strip_suffix('\\') {
+                Some(line) => {
+                    entry.push_str(line);
+                    entry.push('\n');
+                }
+                None => {
+                    entry.push_str(line);
+
+                    if !entry.is_empty() {
+                        self.entries.push(std::mem::take(&mut entry));
+                    }
+                }
+            }
+        }
+
+        self.truncate();
+        self.reset();
+
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let skip = self.entries.len().saturating_sub(self.file_size);
+        let mut contents = String::new();
+
+        // An entry of several lines has a backslash before each line feed in it, so it's read
+        // back as one. One that ends with a backslash itself gets a space after it, which
+        // doesn't change the command.
+        for entry in self.entries.iter().skip(skip) {
+            contents.push_str(&entry.replace('\n', "\\\n"));
+
+            if entry.ends_with('\\') {
+                contents.push(' ');
+            }
+            contents.push('\n');
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn push(&mut self, entry: &str) {
+        if !entry.trim().is_empty() {
+            self.entries.push(entry.to_string());
+            self.truncate();
+        }
+
+        self.reset();
+    }
+
+    /// Moves one entry back. The line being edited is kept so `newer` can restore it.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        if self.position == 0 {
+            return None;
+        }
+
+        if self.position == self.entries.len() {
+            self.draft = Some(current.to_string());
+        }
+
+        self.position -= 1;
+
+        Some(&self.entries[self.position])
+    }
+
+    pub fn newer(&mut self) -> Option<&str> {
+        if self.position >= self.entries.len() {
+            return None;
+        }
+
+        self.position += 1;
+
+        match self.entries.get(self.position) {
+            Some(entry) => Some(entry),
+            None => Some(self.draft.as_deref().unwrap_or("")),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = self.entries.len();
+        self.draft = None;
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.size {
+            let excess = self.entries.len() - self.size;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+fn read_limit(variables: &Variables, name: &str) -> Option<usize> {
+    variables.get(name).and_then(|value| value.parse().ok())
+}
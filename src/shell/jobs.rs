@@ -0,0 +1,439 @@
+use std::{
+    fmt::Display,
+    io::{self, Error, ErrorKind, IsTerminal, Write},
+};
+
+use rustix::{
+    io::Errno,
+    process::{self, Pid, Signal, WaitOptions, WaitStatus},
+    termios,
+};
+
+/// Status of a job stopped with Ctrl-Z, 128 + SIGTSTP.
+pub const STOPPED_STATUS: i32 = 148;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum JobState {
+    Running,
+    Stopped,
+    Done(i32),
+}
+
+impl Display for JobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobState::Running => write!(f, "Running"),
+            JobState::Stopped => write!(f, "Stopped"),
+            JobState::Done(0) => write!(f, "Done"),
+            JobState::Done(status) => write!(f, "Exit {}", status),
+        }
+    }
+}
+
+struct Process {
+    pid: Pid,
+    status: Option<i32>,
+}
+
+/// The processes started for one pipeline.
+pub struct Job {
+    pub id: usize,
+    pub command: String,
+    pub state: JobState,
+    /// Process group of the job, `None` when its processes stay in the shell's group.
+    pgid: Option<Pid>,
+    processes: Vec<Process>,
+    /// The signal that last stopped one of the processes.
+    stop_signal: Option<u32>,
+}
+
+impl Job {
+    /// A running job for `pids`. With `grouped` the first process leads the job's process group.
+    pub fn new(command: &str, pids: &[u32], grouped: bool) -> Self {
+        let processes: Vec<Process> = pids
+            .iter()
+            .filter_map(|pid| Pid::from_raw(*pid as i32))
+            .map(|pid| Process { pid, status: None })
+            .collect();
+
+        Self {
+            id: 0,
+            command: command.to_string(),
+            state: JobState::Running,
+            pgid: processes.first().filter(|_| grouped).map(|p| p.pid),
+            processes,
+            stop_signal: None,
+        }
+    }
+
+    /// Pid of the first process, the leader of the job's process group.
+    pub fn pid(&self) -> Option<i32> {
+        self.processes.first().map(|p| p.pid.as_raw_nonzero().get())
+    }
+
+    /// Pid of the last process of the pipeline, what `$!` expands to.
+    pub fn last_pid(&self) -> Option<i32> {
+        self.processes.last().map(|p| p.pid.as_raw_nonzero().get())
+    }
+
+    /// Exit statuses of the processes, in pipeline order.
+    pub fn statuses(&self) -> Vec<i32> {
+        self.processes
+            .iter()
+            .map(|p| p.status.unwrap_or(0))
+            .collect()
+    }
+
+    fn signal(&self, signal: Signal) -> Result<(), Error> {
+        match self.pgid {
+            Some(pgid) => process::kill_process_group(pgid, signal)?,
+            None => {
+                for process in self.processes.iter().filter(|p| p.status.is_none()) {
+                    process::kill_process(process.pid, signal)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collects the status changes of the processes that haven't exited. With `WNOHANG` it
+    /// doesn't block; otherwise it returns once every process exited or one of them stopped.
+    fn wait(&mut self, options: WaitOptions) -> Result<(), Error> {
+        for process in self.processes.iter_mut().filter(|p| p.status.is_none()) {
+            let status = match process::waitpid(Some(process.pid), options) {
+                Ok(status) => status,
+                // Someone else already reaped it, like a `wait` without job table entry.
+                Err(Errno::CHILD) => {
+                    process.status = Some(127);
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            match status {
+                Some(status) if status.stopped() => {
+                    self.state = JobState::Stopped;
+                    self.stop_signal = status.stopping_signal();
+                    return Ok(());
+                }
+                Some(status) if status.continued() => self.state = JobState::Running,
+                Some(status) => process.status = Some(exit_status(status)),
+                None => {}
+            }
+        }
+
+        if self.processes.iter().all(|p| p.status.is_some()) {
+            let last = self.processes.last().and_then(|p| p.status);
+            self.state = JobState::Done(last.unwrap_or(0));
+        }
+
+        Ok(())
+    }
+}
+
+/// The jobs started by the shell that are running in the background or stopped.
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+    /// Job ids from least to most recently used. The last one is the current job `%+` and the
+    /// one before it the previous job `%-`.
+    order: Vec<usize>,
+    /// Whether the shell owns the terminal and hands it to the foreground job.
+    control: bool,
+    /// Pid of the last process started in the background, for `$!`.
+    pub last_pid: Option<i32>,
+}
+
+impl Jobs {
+    /// Turns on job control when the shell runs on a terminal. The shell itself then ignores
+    /// the signals that stop a job.
+    pub fn enable_control(&mut self) {
+        self.control = io::stdin().is_terminal();
+
+        if self.control {
+            ignore_stop_signals();
+        }
+    }
+
+    /// Forgets the jobs and turns job control off, in a subshell whose jobs are the shell's.
+    pub fn forget(&mut self) {
+        self.jobs.clear();
+        self.order.clear();
+        self.control = false;
+    }
+
+    /// Whether foreground jobs get their own process group and the terminal.
+    pub fn has_control(&self) -> bool {
+        self.control
+    }
+
+    /// Adds `job` to the table as the current job and returns its id.
+    pub fn add(&mut self, mut job: Job) -> usize {
+        let id = self.jobs.iter().map(|j| j.id).max().unwrap_or(0) + 1;
+        job.id = id;
+
+        self.order.push(id);
+        self.jobs.push(job);
+
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        self.order.retain(|i| *i != id);
+
+        let index = self.jobs.iter().position(|j| j.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    /// The job one of whose processes has `pid`.
+    pub fn find_pid(&self, pid: i32) -> Option<usize> {
+        self.jobs
+            .iter()
+            .find(|j| {
+                j.processes
+                    .iter()
+                    .any(|p| p.pid.as_raw_nonzero().get() == pid)
+            })
+            .map(|j| j.id)
+    }
+
+    /// Ids of every job in the table, in the order they were started.
+    pub fn ids(&self) -> Vec<usize> {
+        self.jobs.iter().map(|j| j.id).collect()
+    }
+
+    /// Gives the terminal to the process group of a job about to run in the foreground. A job
+    /// that didn't get it still runs, it only stops once it reads the terminal.
+    pub fn give_terminal(&self, pgid: u32) {
+        if let Some(pgid) = Pid::from_raw(pgid as i32).filter(|_| self.control) {
+            let _ = termios::tcsetpgrp(io::stdin(), pgid);
+        }
+    }
+
+    /// Waits for a job in the foreground until it exits or stops. A stopped job goes into the
+    /// table and `None` is returned, otherwise the exit statuses of its processes.
+    pub fn foreground(&mut self, mut job: Job) -> Result<Option<Vec<i32>>, Error> {
+        if let Some(pgid) = job.pgid {
+            self.give_terminal(pgid.as_raw_nonzero().get() as u32);
+        }
+
+        let result = loop {
+            job.state = JobState::Running;
+
+            if let Err(err) = job.wait(WaitOptions::UNTRACED) {
+                break Err(err);
+            }
+
+            // A process that read the terminal before it was handed over stops with SIGTTIN,
+            // it only has to be continued now that its group owns the terminal.
+            let signal = job.stop_signal.take();
+            let by_terminal = matches!(
+                signal,
+                Some(signal) if signal == Signal::Ttin as u32 || signal == Signal::Ttou as u32
+            );
+
+            if job.state == JobState::Stopped && self.control && by_terminal {
+                match job.signal(Signal::Cont) {
+                    Ok(()) => continue,
+                    Err(err) => break Err(err),
+                }
+            }
+
+            break Ok(());
+        };
+
+        if self.control {
+            take_terminal()?;
+        }
+        result?;
+
+        if job.state != JobState::Stopped {
+            return Ok(Some(job.statuses()));
+        }
+
+        let id = match job.id {
+            0 => self.add(job),
+            id => {
+                self.order.push(id);
+                self.jobs.push(job);
+                id
+            }
+        };
+
+        if let Some(line) = self.format(id) {
+            writeln!(io::stderr(), "\n{}", line)?;
+        }
+
+        Ok(None)
+    }
+
+    /// Continues a stopped job, in the foreground or the background. Returns the exit status
+    /// of a foreground job.
+    pub fn resume(&mut self, id: usize, foreground: bool) -> Result<i32, Error> {
+        let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) else {
+            return Err(no_such_job(&format!("%{}", id)));
+        };
+
+        if matches!(job.state, JobState::Done(_)) {
+            self.remove(id);
+            return Err(Error::new(ErrorKind::InvalidInput, "job has terminated"));
+        }
+
+        job.signal(Signal::Cont)?;
+        job.state = JobState::Running;
+
+        if !foreground {
+            self.order.retain(|i| *i != id);
+            self.order.push(id);
+
+            return Ok(0);
+        }
+
+        let Some(job) = self.remove(id) else {
+            return Err(no_such_job(&format!("%{}", id)));
+        };
+
+        match self.foreground(job)? {
+            Some(statuses) => Ok(statuses.last().copied().unwrap_or(0)),
+            None => Ok(STOPPED_STATUS),
+        }
+    }
+
+    /// Blocks until the job has exited, removes it from the table and returns its status.
+    pub fn wait(&mut self, id: usize) -> Result<i32, Error> {
+        let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) else {
+            return Err(no_such_job(&format!("%{}", id)));
+        };
+
+        job.wait(WaitOptions::empty())?;
+        let status = match job.state {
+            JobState::Done(status) => status,
+            _ => 0,
+        };
+
+        self.remove(id);
+
+        Ok(status)
+    }
+
+    /// Checks every job for processes that exited, stopped or continued without blocking.
+    pub fn update(&mut self) -> Result<(), Error> {
+        let options = WaitOptions::NOHANG | WaitOptions::UNTRACED | WaitOptions::CONTINUED;
+
+        for job in self.jobs.iter_mut() {
+            if !matches!(job.state, JobState::Done(_)) {
+                job.wait(options)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the jobs that finished since the last check and returns their `[1]+  Done`
+    /// lines.
+    pub fn notifications(&mut self) -> Vec<String> {
+        if self.jobs.is_empty() || self.update().is_err() {
+            return Vec::new();
+        }
+
+        let done: Vec<usize> = self
+            .jobs
+            .iter()
+            .filter(|j| matches!(j.state, JobState::Done(_)))
+            .map(|j| j.id)
+            .collect();
+
+        done.into_iter()
+            .filter_map(|id| {
+                let line = self.format(id);
+                self.remove(id);
+                line
+            })
+            .collect()
+    }
+
+    /// The `jobs` line of a job, `[1]+  Running                 sleep 10 &`.
+    pub fn format(&self, id: usize) -> Option<String> {
+        let job = self.get(id)?;
+        let marker = match self.order.iter().rev().position(|i| *i == id) {
+            Some(0) => '+',
+            Some(1) => '-',
+            _ => ' ',
+        };
+        let suffix = match job.state {
+            JobState::Running => " &",
+            _ => "",
+        };
+
+        Some(format!(
+            "[{}]{}  {:<24}{}{}",
+            job.id,
+            marker,
+            job.state.to_string(),
+            job.command,
+            suffix
+        ))
+    }
+
+    /// Finds the job a `%n`, `%+`, `%%`, `%-` or `%prefix` spec refers to. No spec means the
+    /// current job.
+    pub fn resolve(&self, spec: Option<&str>) -> Result<usize, Error> {
+        let current = |offset: usize| {
+            self.order
+                .len()
+                .checked_sub(offset + 1)
+                .map(|index| self.order[index])
+        };
+
+        let found =
+            match spec.map(|s| s.strip_prefix('%').unwrap_or(s)) {
+                None | Some("" | "+" | "%") => current(0),
+                Some("-") => current(1),
+                Some(id) if id.chars().all(|ch| ch.is_ascii_digit()) => {
+                    id.parse().ok().filter(|id| self.get(*id).is_some())
+                }
+                Some(prefix) => self.order.iter().rev().copied().find(
+                    |id| matches!(self.get(*id), Some(job) if job.command.starts_with(prefix)),
+                ),
+            };
+
+        match (found, spec) {
+            (Some(id), _) => Ok(id),
+            (None, Some(spec)) => Err(no_such_job(spec)),
+            (None, None) => Err(no_such_job("current")),
+        }
+    }
+}
+
+pub(super) fn exit_status(status: WaitStatus) -> i32 {
+    match (status.exit_status(), status.terminating_signal()) {
+        (Some(code), _) => code as i32,
+        (None, Some(signal)) => 128 + signal as i32,
+        (None, None) => 0,
+    }
+}
+
+fn ignore_stop_signals() {
+    for signal in [libc::SIGTSTP, libc::SIGTTIN, libc::SIGTTOU] {
+        shell_sys::ignore_signal(signal);
+    }
+}
+
+/// Takes the terminal back for the shell. The shell isn't in the foreground group at this
+/// point, so without ignoring SIGTTOU `tcsetpgrp` would stop the shell itself.
+fn take_terminal() -> Result<(), Error> {
+    let previous = shell_sys::ignore_signal(libc::SIGTTOU);
+    let result = termios::tcsetpgrp(io::stdin(), process::getpgrp());
+    shell_sys::restore_signal(libc::SIGTTOU, previous);
+
+    Ok(result?)
+}
+
+fn no_such_job(spec: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("{}: no such job", spec))
+}
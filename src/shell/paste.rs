@@ -0,0 +1,93 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::editor::Editor;
+
+/// Columns a tab takes on the screen. It's drawn as that many spaces, so where the cursor
+/// goes after it doesn't depend on the terminal's tab stops.
+pub const TAB_WIDTH: usize = 4;
+
+/// Text pasted into the line being edited, and the question before running it that
+/// `shopt -s confirmpaste` asks when lines were pasted.
+#[derive(Default)]
+pub struct Paste {
+    /// Where the text pasted last is in the line, highlighted until the next key.
+    highlight: Option<Range<usize>>,
+    /// Whether lines were pasted since the input last ran.
+    lines: bool,
+    /// Waiting for the answer to whether to run the input.
+    confirming: bool,
+}
+
+impl Paste {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts pasted text at the cursor as it is, line feeds and tabs included. Other control
+    /// characters are dropped, an escape sequence would reach the terminal when the line is
+    /// drawn. Nothing runs, even when the text ends with a line break.
+    pub fn insert(&mut self, line: &mut Editor, text: &str) {
+        // Terminals send line breaks as carriage returns.
+        let text: String = text
+            .replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .chars()
+            .filter(|&ch| !ch.is_control() || matches!(ch, '\n' | '\t'))
+            .collect();
+        let text = text.trim_end_matches('\n');
+        let start = line.cursor();
+
+        line.replace(start..start, text);
+        self.highlight = Some(start..line.cursor());
+        self.lines |= text.contains('\n');
+    }
+
+    pub fn highlight(&self) -> Option<Range<usize>> {
+        self.highlight.clone()
+    }
+
+    /// Takes the highlight away, `true` if there was one to draw over.
+    pub fn clear_highlight(&mut self) -> bool {
+        self.highlight.take().is_some()
+    }
+
+    pub fn is_confirming(&self) -> bool {
+        self.confirming
+    }
+
+    /// Whether Enter on `line` asks before running it: lines were pasted into it and
+    /// `enabled`, which is `confirmpaste`. The question is returned and waits for `answer`.
+    pub fn confirm(&mut self, line: &Editor, enabled: bool) -> Option<String> {
+        if !enabled || !self.lines || !line.text().contains('\n') {
+            return None;
+        }
+
+        self.confirming = true;
+
+        Some(format!(
+            "Run the {} pasted lines? [y/N] ",
+            line.text().lines().count()
+        ))
+    }
+
+    /// The answer to the question: `y` runs the input, any other key goes back to editing it.
+    pub fn answer(&mut self, key_event: KeyEvent) -> bool {
+        self.confirming = false;
+
+        matches!(key_event.code, KeyCode::Char('y' | 'Y'))
+    }
+
+    /// The input ran, so what's pasted next is asked about again.
+    pub fn reset(&mut self) {
+        self.highlight = None;
+        self.lines = false;
+        self.confirming = false;
+    }
+}
+
+/// `text` the way the line is drawn, each tab as `TAB_WIDTH` spaces.
+pub fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
@@ -0,0 +1,294 @@
+use std::{
+    env,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use shell_sys::hostname;
+
+use crate::util::unicode;
+
+use super::{
+    core::{ShellCommandProvider, ShellInterpreter},
+    session::Session,
+};
+
+/// Starts and ends a part of the prompt that takes no room on the screen, what `\[` and `\]`
+/// turn into.
+const HIDDEN_START: char = '\x01';
+const HIDDEN_END: char = '\x02';
+
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A prompt ready to print, with the room its last line takes on the screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prompt {
+    text: String,
+    width: usize,
+}
+
+impl Default for Prompt {
+    fn default() -> Self {
+        Self::plain("$ ")
+    }
+}
+
+impl Prompt {
+    /// Expands a `PS1` or `PS2` value: the bash escapes like `\w` first, then parameters and
+    /// command substitutions. A part between `\[` and `\]`, and any ANSI escape sequence,
+    /// doesn't count in the width.
+    pub fn new<T, SI: ShellInterpreter<T>, SCC: ShellCommandProvider<T>>(
+        template: &str,
+        session: &mut Session,
+    ) -> Self {
+        let decoded = decode(template, session);
+        let expanded = match SI::expand::<SCC>(&decoded, session) {
+            Ok(expanded) => expanded,
+            Err(_) => unescape(&decoded),
+        };
+
+        let last_line = expanded.rsplit('\n').next().unwrap_or_default();
+
+        Self {
+            width: display_width(last_line),
+            text: expanded.replace([HIDDEN_START, HIDDEN_END], ""),
+        }
+    }
+
+    /// A prompt printed as it is.
+    pub fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            width: display_width(text),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The lines before the one the input is typed on, with their line feeds.
+    pub fn head(&self) -> &str {
+        &self.text[..self.text.len() - self.last_line().len()]
+    }
+
+    /// The line the input is typed on, what redrawing the line prints again.
+    pub fn last_line(&self) -> &str {
+        self.text.rsplit('\n').next().unwrap_or_default()
+    }
+
+    /// Columns the last line takes, where the input starts.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+/// Replaces the backslash escapes of a prompt. What they give is escaped for the expansion
+/// that comes after, so a `$` in the directory name stays as it is.
+fn decode(template: &str, session: &Session) -> String {
+    let mut decoded = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+
+        let Some(escape) = chars.next() else {
+            decoded.push(ch);
+            break;
+        };
+
+        let value = match escape {
+            'u' => user(session),
+            'h' => hostname().split('.').next().unwrap_or_default().to_string(),
+            'H' => hostname(),
+            'w' => working_directory(session, false),
+            'W' => working_directory(session, true),
+            's' => shell_name(session),
+            '$' => match rustix::process::getuid().is_root() {
+                true => String::from("#"),
+                false => String::from("$"),
+            },
+            '?' => session.last_status.to_string(),
+            'j' => session.jobs.ids().len().to_string(),
+            't' | 'T' | 'A' | '@' | 'd' => time(escape),
+            'n' => String::from("\n"),
+            'r' => String::from("\r"),
+            'a' => String::from("\x07"),
+            'e' => String::from("\x1b"),
+            '\\' => String::from("\\"),
+            '[' => {
+                decoded.push(HIDDEN_START);
+                continue;
+            }
+            ']' => {
+                decoded.push(HIDDEN_END);
+                continue;
+            }
+            '0'..='7' => {
+                let mut code = escape.to_digit(8).unwrap_or_default();
+
+                for _ in 0..2 {
+                    match chars.peek().and_then(|next| next.to_digit(8)) {
+                        Some(digit) => {
+                            code = code * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+
+                char::from_u32(code).map(String::from).unwrap_or_default()
+            }
+            other => {
+                decoded.push(ch);
+                decoded.push(other);
+                continue;
+            }
+        };
+
+        for ch in value.chars() {
+            if matches!(ch, '$' | '`' | '\\') {
+                decoded.push('\\');
+            }
+            decoded.push(ch);
+        }
+    }
+
+    decoded
+}
+
+/// Undoes the escaping of `decode`, for a prompt whose expansion failed.
+fn unescape(decoded: &str) -> String {
+    let mut text = String::new();
+    let mut chars = decoded.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some('$' | '`' | '\\')) => text.extend(chars.next()),
+            _ => text.push(ch),
+        }
+    }
+
+    text
+}
+
+/// Columns `text` takes on the screen, without what's between `\[` and `\]` and without ANSI
+/// escape sequences.
+pub fn display_width(text: &str) -> usize {
+    let mut visible = String::new();
+    let mut hidden = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            HIDDEN_START => hidden = true,
+            HIDDEN_END => hidden = false,
+            _ if hidden => {}
+            '\x1b' => match chars.next() {
+                // CSI, like the colors in `\e[1;32m`, ends with a letter or one of `@[\]^_`{|}~`.
+                Some('[') => {
+                    for next in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&next) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, like a window title, ends with BEL or `\e\`.
+                Some(']') => {
+                    while let Some(next) = chars.next() {
+                        if next == '\x07' || (next == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => visible.push(ch),
+        }
+    }
+
+    unicode::width(&visible)
+}
+
+fn user(session: &Session) -> String {
+    session
+        .variables
+        .get("USER")
+        .or_else(|| session.variables.get("LOGNAME"))
+        .map(String::from)
+        .unwrap_or_else(|| rustix::process::getuid().as_raw().to_string())
+}
+
+fn shell_name(session: &Session) -> String {
+    let name = session.name.trim_start_matches('-');
+
+    Path::new(name)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// The current directory with the home directory shortened to `~`, or only its last
+/// component.
+fn working_directory(session: &Session, basename: bool) -> String {
+    let cwd = match env::current_dir() {
+        Ok(cwd) => cwd.display().to_string(),
+        Err(_) => session.variables.get("PWD").unwrap_or_default().to_string(),
+    };
+    let home = session
+        .variables
+        .get("HOME")
+        .map(|home| home.trim_end_matches('/'))
+        .filter(|home| !home.is_empty());
+
+    match home {
+        Some(home) if cwd == home => String::from("~"),
+        Some(home) if !basename && cwd.starts_with(&format!("{}/", home)) => {
+            format!("~{}", &cwd[home.len()..])
+        }
+        _ if basename && cwd != "/" => cwd.rsplit('/').next().unwrap_or_default().to_string(),
+        _ => cwd,
+    }
+}
+
+/// `\t` is `HH:MM:SS`, `\T` the same in 12-hour format, `\A` is `HH:MM`, `\@` is `HH:MM AM`
+/// and `\d` is `Sun Oct 18`.
+fn time(escape: char) -> String {
+    let tm = local_time();
+    let hour12 = match tm.tm_hour % 12 {
+        0 => 12,
+        hour => hour,
+    };
+
+    match escape {
+        't' => format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec),
+        'T' => format!("{:02}:{:02}:{:02}", hour12, tm.tm_min, tm.tm_sec),
+        'A' => format!("{:02}:{:02}", tm.tm_hour, tm.tm_min),
+        '@' => format!(
+            "{:02}:{:02} {}",
+            hour12,
+            tm.tm_min,
+            if tm.tm_hour < 12 { "AM" } else { "PM" }
+        ),
+        _ => format!(
+            "{} {} {:02}",
+            DAYS[tm.tm_wday as usize % 7],
+            MONTHS[tm.tm_mon as usize % 12],
+            tm.tm_mday
+        ),
+    }
+}
+
+fn local_time() -> shell_sys::tm {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    shell_sys::local_time(now as i64)
+}
@@ -0,0 +1,207 @@
+use std::{
+    fs::File,
+    io::{BufReader, Error, ErrorKind, Read},
+    path::Path,
+};
+
+use crate::util::error::is_abort;
+
+use super::{
+    core::{ShellCommandProvider, ShellInterpreter, ShellTokenizer},
+    session::{Flow, Location, Session},
+};
+
+/// Runs commands without a terminal: a `-c` string, a script file or piped stdin.
+pub struct Script {
+    session: Session,
+}
+
+/// How running the lines of an input ended.
+enum End {
+    Finished,
+    /// `exit` ran.
+    Exited,
+    /// `return` ran in a sourced file.
+    Returned,
+    /// A syntax error or a failed read ended the input early, with the status to report.
+    Failed(i32),
+}
+
+impl Script {
+    pub fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    /// Runs every command of `input` and returns the status the shell exits with. Commands
+    /// are read a line at a time, a command that needs more lines, like a loop or a
+    /// here-document, runs once it's complete.
+    pub fn run<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+        &mut self,
+        input: impl Read,
+    ) -> i32 {
+        let name = self.session.name.clone();
+
+        match run_lines::<T, SI, ST, SCC>(&mut self.session, input, &name, false) {
+            End::Failed(status) => status,
+            End::Finished | End::Exited | End::Returned => self.session.last_status,
+        }
+    }
+}
+
+/// Runs a file in the session, the way `source` and the startup files do. Errors are
+/// reported with the file and line, and the rest of the file still runs. Fails with
+/// `Interrupted` when the file runs `exit`.
+pub fn source<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+    session: &mut Session,
+    path: &Path,
+) -> Result<(), Error> {
+    let file = File::open(path)?;
+
+    let loop_depth = std::mem::take(&mut session.loop_depth);
+    session.call_depth += 1;
+
+    let end = run_lines::<T, SI, ST, SCC>(
+        session,
+        BufReader::new(file),
+        &path.display().to_string(),
+        true,
+    );
+
+    session.call_depth -= 1;
+    session.loop_depth = loop_depth;
+
+    match end {
+        End::Exited => Err(Error::new(ErrorKind::Interrupted, "")),
+        End::Returned => {
+            session.flow = None;
+            Ok(())
+        }
+        End::Failed(status) => {
+            session.last_status = status;
+            Ok(())
+        }
+        End::Finished => Ok(()),
+    }
+}
+
+/// Runs the commands of `input`, which errors name `origin`. With `keep_going`, a syntax error
+/// only skips the command it's in.
+fn run_lines<T, SI: ShellInterpreter<T>, ST: ShellTokenizer<T>, SCC: ShellCommandProvider<T>>(
+    session: &mut Session,
+    mut input: impl Read,
+    origin: &str,
+    keep_going: bool,
+) -> End {
+    let outer = session.location.take();
+    let mut pending = String::new();
+    let mut line_number = 0;
+    let mut start = 0;
+
+    let end = loop {
+        let line = match read_line(&mut input) {
+            Ok(Some(line)) => line,
+            Ok(None) => break End::Finished,
+            Err(err) => {
+                print_error(session, origin, line_number + 1, &err);
+                break End::Failed(1);
+            }
+        };
+
+        line_number += 1;
+
+        pending = match pending.is_empty() {
+            true => {
+                start = line_number;
+                line
+            }
+            false => format!("{}\n{}", pending, line),
+        };
+
+        let result = ST::tokenize(pending.trim()).and_then(|t| SI::check(&t).map(|()| t));
+
+        let tokens = match result {
+            Ok(tokens) => tokens,
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => continue,
+            Err(err) => {
+                print_error(session, origin, start, &err);
+                pending.clear();
+
+                match keep_going {
+                    true => {
+                        session.last_status = 2;
+                        continue;
+                    }
+                    false => break End::Failed(2),
+                }
+            }
+        };
+        pending.clear();
+
+        session.location = Some(Location {
+            file: origin.to_string(),
+            line: start,
+        });
+
+        match SI::run::<SCC>(&tokens, session) {
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::Interrupted => break End::Exited,
+            // The interpreter only gives up on syntax errors and on an `Abort`, which already
+            // set the status.
+            Err(err) => {
+                print_error(session, origin, start, &err);
+
+                let status = match is_abort(&err) {
+                    true => session.last_status,
+                    false => 2,
+                };
+
+                match keep_going {
+                    true => session.last_status = status,
+                    false => break End::Failed(status),
+                }
+            }
+        }
+
+        if session.flow == Some(Flow::Return) {
+            break End::Returned;
+        }
+    };
+
+    session.location = outer;
+
+    if !pending.is_empty() {
+        if let Err(err) = ST::tokenize(pending.trim()).and_then(|t| SI::check(&t)) {
+            print_error(session, origin, start, &err);
+            return End::Failed(2);
+        }
+    }
+
+    end
+}
+
+fn print_error(session: &Session, origin: &str, line: usize, err: &Error) {
+    if !err.to_string().is_empty() {
+        let message = format!("{}: line {}: {}\n", origin, line, err);
+        let _ = session.write_stderr(message.as_bytes());
+    }
+}
+
+/// Reads a line without its newline, one byte at a time so none of the input after it is
+/// taken away from the commands. `None` at the end of the input.
+fn read_line(input: &mut impl Read) -> Result<Option<String>, Error> {
+    let mut line = Vec::new();
+    let mut byte = [0; 1];
+
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) if line.is_empty() => return Ok(None),
+            Ok(0) => break,
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) => line.push(byte[0]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
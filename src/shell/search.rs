@@ -0,0 +1,71 @@
+use super::history::History;
+
+pub struct ReverseSearch {
+    query: String,
+    found: Option<usize>,
+    failed: bool,
+    original: String,
+}
+
+impl ReverseSearch {
+    pub fn new(original: &str) -> Self {
+        Self {
+            query: String::new(),
+            found: None,
+            failed: false,
+            original: original.to_string(),
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    pub fn matched<'a>(&self, history: &'a History) -> Option<&'a str> {
+        self.found.and_then(|index| history.get(index))
+    }
+
+    /// Narrows the search, keeping the current match if it still contains the query.
+    pub fn push(&mut self, ch: char, history: &History) {
+        self.query.push(ch);
+
+        let before = match self.found {
+            Some(index) => index + 1,
+            None => history.entries().len(),
+        };
+
+        self.search(history, before);
+    }
+
+    pub fn pop(&mut self, history: &History) {
+        self.query.pop();
+        self.search(history, history.entries().len());
+    }
+
+    pub fn older(&mut self, history: &History) {
+        let before = match self.found {
+            Some(index) => index,
+            None => history.entries().len(),
+        };
+
+        self.search(history, before);
+    }
+
+    fn search(&mut self, history: &History, before: usize) {
+        match history.search(&self.query, before) {
+            Some(index) => {
+                self.found = Some(index);
+                self.failed = false;
+            }
+            None => self.failed = true,
+        }
+    }
+}
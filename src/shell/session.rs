@@ -0,0 +1,207 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fmt::Display,
+    io::{self, Error, ErrorKind, Write},
+    os::fd::OwnedFd,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::parser::Function;
+
+use super::{completion::Spec, jobs::Jobs, variables::Variables};
+
+/// State that lives for the whole shell session and is shared by the interpreter and builtins.
+pub struct Session {
+    pub options: Options,
+    pub last_status: i32,
+    pub variables: Variables,
+    /// Stdin, stdout and stderr of the commands being run, while a compound command or a
+    /// `$(...)` redirects them.
+    pub streams: [Stream; 3],
+    pub jobs: Jobs,
+    /// Name of the shell or script, `$0`.
+    pub name: String,
+    /// The positional parameters `$1`, `$2`...
+    pub positional: Vec<String>,
+    /// Set by `break`, `continue` and `return` until the loop or function they refer to
+    /// handles it.
+    pub flow: Option<Flow>,
+    /// Number of loops the running command is nested in, inside the current function.
+    pub loop_depth: usize,
+    pub functions: HashMap<String, Rc<Function>>,
+    pub aliases: BTreeMap<String, String>,
+    /// How `complete` completes the arguments of each command.
+    pub completions: BTreeMap<String, Spec>,
+    /// Number of functions and sourced files being run, `return` is only valid inside one.
+    pub call_depth: usize,
+    /// Where the running command comes from when it's read from a file, errors are reported
+    /// with it.
+    pub location: Option<Location>,
+    /// Set when Ctrl-C or Ctrl-\\ reaches the interactive shell while it runs commands, until
+    /// it shows the prompt again.
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// A line of a script or a sourced file.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: line {}", self.file, self.line)
+    }
+}
+
+/// A standard stream of the commands being run.
+#[derive(Default, Debug)]
+pub enum Stream {
+    /// The shell's own.
+    #[default]
+    Inherited,
+    Fd(OwnedFd),
+    /// Closed with `n>&-`, reading or writing it fails.
+    Closed,
+}
+
+/// A jump out of the commands of a loop, with how many loops it goes through, or out of a
+/// function.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Flow {
+    Break(usize),
+    Continue(usize),
+    Return,
+}
+
+#[derive(Default)]
+pub struct Options {
+    pub pipefail: bool,
+    pub nullglob: bool,
+    pub failglob: bool,
+    pub globstar: bool,
+    pub noclobber: bool,
+    /// Vi editing mode instead of emacs.
+    pub vi: bool,
+    /// Asks before running a command of several lines that was pasted.
+    pub confirmpaste: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            options: Options::default(),
+            last_status: 0,
+            variables: Variables::from_env(),
+            streams: Default::default(),
+            jobs: Jobs::default(),
+            name: env::args().next().unwrap_or_else(|| String::from("shell")),
+            positional: Vec::new(),
+            flow: None,
+            loop_depth: 0,
+            functions: HashMap::new(),
+            aliases: BTreeMap::new(),
+            completions: BTreeMap::new(),
+            call_depth: 0,
+            location: None,
+            interrupted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether Ctrl-C reached the shell, or ended the last command, so the rest of the input
+    /// shouldn't run.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed) || self.last_status == 128 + libc::SIGINT
+    }
+
+    /// Writes to the stdout of the running command, for builtins whose output isn't a
+    /// line of text.
+    pub fn write_stdout(&self, bytes: &[u8]) -> Result<(), Error> {
+        self.write_stream(1, bytes)
+    }
+
+    /// Writes to the stderr of the running command, where errors are reported.
+    pub fn write_stderr(&self, bytes: &[u8]) -> Result<(), Error> {
+        self.write_stream(2, bytes)
+    }
+
+    fn write_stream(&self, index: usize, mut bytes: &[u8]) -> Result<(), Error> {
+        let fd = match &self.streams[index] {
+            Stream::Fd(fd) => fd,
+            Stream::Inherited if index == 1 => return io::stdout().write_all(bytes),
+            Stream::Inherited => return io::stderr().write_all(bytes),
+            Stream::Closed => return Err(closed()),
+        };
+
+        while !bytes.is_empty() {
+            match rustix::io::write(fd, bytes) {
+                Ok(0) => return Err(Error::from(ErrorKind::WriteZero)),
+                Ok(written) => bytes = &bytes[written..],
+                Err(rustix::io::Errno::INTR) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Options {
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "pipefail" => Some(self.pipefail),
+            "nullglob" => Some(self.nullglob),
+            "failglob" => Some(self.failglob),
+            "globstar" => Some(self.globstar),
+            "noclobber" => Some(self.noclobber),
+            "vi" => Some(self.vi),
+            "emacs" => Some(!self.vi),
+            "confirmpaste" => Some(self.confirmpaste),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: bool) -> Option<()> {
+        match name {
+            "pipefail" => self.pipefail = value,
+            "nullglob" => self.nullglob = value,
+            "failglob" => self.failglob = value,
+            "globstar" => self.globstar = value,
+            "noclobber" => self.noclobber = value,
+            "vi" => self.vi = value,
+            // Line editing can't be turned off, `set +o emacs` leaves it as it is.
+            "emacs" => self.vi &= !value,
+            "confirmpaste" => self.confirmpaste = value,
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    /// Options toggled with `set -o`.
+    pub fn names() -> Vec<&'static str> {
+        vec!["emacs", "noclobber", "pipefail", "vi"]
+    }
+
+    /// Options toggled with `shopt`.
+    pub fn shopt_names() -> Vec<&'static str> {
+        vec!["confirmpaste", "failglob", "globstar", "nullglob"]
+    }
+}
+
+/// The error of reading or writing a closed stream.
+pub fn closed() -> Error {
+    Error::new(ErrorKind::InvalidInput, "Bad file descriptor")
+}
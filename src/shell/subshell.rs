@@ -0,0 +1,69 @@
+use std::{
+    io::{self, Error, Write},
+    process::Command,
+};
+
+use rustix::process::{self, Pid, WaitOptions};
+use shell_sys::Fork;
+
+use super::{jobs::exit_status, session::Session};
+
+/// Signals the shell catches or ignores, which a subshell takes as usual. With SIGPIPE it
+/// ends when the stage it writes to exits, like an external command.
+const RESET_SIGNALS: [libc::c_int; 6] = [
+    libc::SIGINT,
+    libc::SIGPIPE,
+    libc::SIGQUIT,
+    libc::SIGTSTP,
+    libc::SIGTTIN,
+    libc::SIGTTOU,
+];
+
+/// Runs `run` in a forked copy of the shell, a subshell: what it changes, like variables,
+/// functions or the current directory, stays in the copy. The copy exits with the status
+/// `run` returns. Returns its pid.
+///
+/// Only the calling thread goes on in the copy, which is sound since the shell has no other:
+/// its runtime runs on the main thread, here-documents are files and the output of `$(...)`
+/// is read by the thread that forked.
+pub fn fork(session: &mut Session, run: impl FnOnce(&mut Session) -> i32) -> Result<u32, Error> {
+    // What's still buffered would be written twice otherwise, once by each process.
+    io::stdout().flush()?;
+
+    match shell_sys::fork()? {
+        Fork::Child => {
+            for signal in RESET_SIGNALS {
+                shell_sys::default_signal(signal);
+            }
+            session.jobs.forget();
+
+            let status = run(session);
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+
+            // It never returns into the caller, nor runs the parent's exit handlers.
+            shell_sys::exit_now(status)
+        }
+        Fork::Parent(pid) => Ok(pid),
+    }
+}
+
+/// Gives an external command the default signal dispositions, since the ones the shell ignores
+/// would stay ignored in it.
+pub fn restore_signals(command: &mut Command) {
+    shell_sys::default_signals_on_exec(command, &RESET_SIGNALS);
+}
+
+/// Waits for a subshell started with `fork` to exit and returns its status.
+pub fn wait(pid: u32) -> Result<i32, Error> {
+    let pid = Pid::from_raw(pid as i32).ok_or_else(|| Error::from(io::ErrorKind::InvalidInput))?;
+
+    loop {
+        match process::waitpid(Some(pid), WaitOptions::empty()) {
+            Ok(Some(status)) => return Ok(exit_status(status)),
+            Ok(None) => {}
+            Err(rustix::io::Errno::INTR) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
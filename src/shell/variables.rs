@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::{Error, ErrorKind},
+};
+
+/// Shell variables. Exported ones are mirrored into the process environment so every child
+/// inherits them.
+#[derive(Default)]
+pub struct Variables {
+    values: HashMap<String, Variable>,
+    /// For every function being run, the variables its `local` replaced, restored when it
+    /// returns. `None` for one that wasn't set.
+    scopes: Vec<HashMap<String, Option<Variable>>>,
+}
+
+/// A variable as it was before `Variables::set_for_command`, `None` for one that wasn't set.
+pub struct Saved(String, Option<Variable>);
+
+#[derive(Default)]
+struct Variable {
+    value: Option<String>,
+    exported: bool,
+    readonly: bool,
+}
+
+impl Variables {
+    pub fn from_env() -> Self {
+        let values = env::vars()
+            .map(|(name, value)| {
+                let variable = Variable {
+                    value: Some(value),
+                    exported: true,
+                    readonly: false,
+                };
+
+                (name, variable)
+            })
+            .collect();
+
+        Self {
+            values,
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name)?.value.as_deref()
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let variable = self.values.entry(name.to_string()).or_default();
+
+        if variable.readonly {
+            return Err(readonly_error(name));
+        }
+
+        if variable.exported {
+            env::set_var(name, value);
+        }
+
+        variable.value = Some(value.to_string());
+
+        Ok(())
+    }
+
+    pub fn unset(&mut self, name: &str) -> Result<(), Error> {
+        match self.values.get(name) {
+            Some(variable) if variable.readonly => return Err(readonly_error(name)),
+            Some(variable) if variable.exported => env::remove_var(name),
+            _ => {}
+        }
+
+        self.values.remove(name);
+
+        Ok(())
+    }
+
+    /// Marks the variable as exported, or takes the mark away when `exported` is false.
+    pub fn export(&mut self, name: &str, exported: bool) {
+        let variable = self.values.entry(name.to_string()).or_default();
+        variable.exported = exported;
+
+        match (&variable.value, exported) {
+            (Some(value), true) => env::set_var(name, value),
+            _ => env::remove_var(name),
+        }
+    }
+
+    pub fn set_readonly(&mut self, name: &str) {
+        self.values.entry(name.to_string()).or_default().readonly = true;
+    }
+
+    pub fn is_exported(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|v| v.exported)
+    }
+
+    pub fn is_readonly(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|v| v.readonly)
+    }
+
+    /// Starts the scope of a function call.
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Ends the innermost scope and brings back the variables its locals hid.
+    pub fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        for (name, saved) in scope {
+            self.put_back(name, saved);
+        }
+    }
+
+    /// Sets an exported variable for one command, the `NAME=value` before a builtin. Returns
+    /// the variable it hid, for `restore`.
+    pub fn set_for_command(&mut self, name: &str, value: &str) -> Result<Saved, Error> {
+        if self.is_readonly(name) {
+            return Err(readonly_error(name));
+        }
+
+        let saved = self.values.remove(name);
+        self.set(name, value)?;
+        self.export(name, true);
+
+        Ok(Saved(name.to_string(), saved))
+    }
+
+    /// Brings back the variable `set_for_command` hid.
+    pub fn restore(&mut self, saved: Saved) {
+        let Saved(name, variable) = saved;
+        self.put_back(name, variable);
+    }
+
+    /// Replaces the variable with `saved`, unsetting it for `None`.
+    fn put_back(&mut self, name: String, saved: Option<Variable>) {
+        if self.is_exported(&name) {
+            env::remove_var(&name);
+        }
+
+        match saved {
+            Some(variable) => {
+                if let (true, Some(value)) = (variable.exported, &variable.value) {
+                    env::set_var(&name, value);
+                }
+                self.values.insert(name, variable);
+            }
+            None => {
+                self.values.remove(&name);
+            }
+        }
+    }
+
+    /// Makes the variable local to the innermost function, unset until it's assigned. Callees
+    /// see it too, the scoping is dynamic.
+    pub fn make_local(&mut self, name: &str) -> Result<(), Error> {
+        if self.is_readonly(name) {
+            return Err(readonly_error(name));
+        }
+
+        let Some(scope) = self.scopes.last_mut() else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "local: can only be used in a function",
+            ));
+        };
+
+        if scope.contains_key(name) {
+            return Ok(());
+        }
+
+        let saved = self.values.remove(name);
+
+        if saved.as_ref().is_some_and(|v| v.exported) {
+            env::remove_var(name);
+        }
+
+        scope.insert(name.to_string(), saved);
+        self.values.insert(name.to_string(), Variable::default());
+
+        Ok(())
+    }
+
+    /// Names of every declared variable in sorted order, including ones without a value.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.values.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+
+        names
+    }
+}
+
+/// Whether `name` can be used as a variable name.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Splits a `NAME=value` word. Returns `None` when the part before `=` isn't a valid name.
+pub fn split_assignment(word: &str) -> Option<(&str, &str)> {
+    let (name, value) = word.split_once('=')?;
+
+    match is_valid_name(name) {
+        true => Some((name, value)),
+        false => None,
+    }
+}
+
+/// Quotes a value so it can be read back by the shell, leaving plain words as they are.
+pub fn quote(value: &str) -> String {
+    let is_plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "_-./:,+=%@".contains(ch));
+
+    match is_plain {
+        true => value.to_string(),
+        false => format!("'{}'", value.replace('\'', "'\\''")),
+    }
+}
+
+fn readonly_error(name: &str) -> Error {
+    Error::new(
+        ErrorKind::PermissionDenied,
+        format!("{}: readonly variable", name),
+    )
+}
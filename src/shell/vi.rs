@@ -0,0 +1,605 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::util::unicode;
+
+use super::{editor::Editor, history::History};
+
+/// The highest count a command takes, so a huge one can't loop or allocate without end.
+const MAX_COUNT: usize = 10_000;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Mode {
+    #[default]
+    Insert,
+    Normal,
+}
+
+/// What became of a key given to the vi engine.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The line or the mode changed, the line needs to be drawn again.
+    Handled,
+    /// The key isn't a vi command and goes through the usual handling, like Enter or Tab.
+    Unhandled,
+    /// The command couldn't be done, like `h` at the start of the line.
+    Bell,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Motion {
+    Left,
+    Right,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    Start,
+    End,
+    Find { ch: char, forward: bool, till: bool },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Command {
+    Move(Motion),
+    Operate(Operator, Motion),
+    /// `dd`, `cc` and `yy` work on the whole line.
+    Line(Operator),
+    DeleteChar,
+    Put {
+        after: bool,
+    },
+    /// `i`, or `a`, `I` and `A` with the motion to where the text goes.
+    Insert(Option<Motion>),
+    Undo,
+    Repeat,
+    Search,
+    SearchAgain,
+    Older,
+    Newer,
+}
+
+impl Command {
+    /// Whether `.` repeats the command.
+    fn is_change(&self) -> bool {
+        match self {
+            Command::Operate(operator, _) | Command::Line(operator) => *operator != Operator::Yank,
+            Command::DeleteChar | Command::Put { .. } | Command::Insert(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The vi editing mode, `set -o vi`: keys typed in normal mode are commands with motions and
+/// operators, and Esc goes from insert mode to normal mode.
+#[derive(Default)]
+pub struct Vi {
+    mode: Mode,
+    /// Keys of the normal mode command typed so far, like `2d` waiting for a motion.
+    keys: String,
+    /// Keys of the last change, what `.` does again. Insert mode keys are added until Esc when
+    /// the change goes into insert mode.
+    last_change: Vec<KeyEvent>,
+    recording: bool,
+    replaying: bool,
+    /// What `d`, `c`, `y` and `x` took, what `p` puts.
+    register: String,
+    /// The line and the cursor before each change, for `u`.
+    undo: Vec<(String, usize)>,
+    /// The `/` search being typed.
+    query: Option<String>,
+    /// The last search and the history entry it found, for `n`.
+    found: Option<(String, usize)>,
+}
+
+impl Vi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The `/` search being typed, shown instead of the line.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Starts over in insert mode for a new line.
+    pub fn reset(&mut self) {
+        self.mode = Mode::Insert;
+        self.keys.clear();
+        self.recording = false;
+        self.undo.clear();
+        self.query = None;
+    }
+
+    pub fn handle(
+        &mut self,
+        key_event: KeyEvent,
+        line: &mut Editor,
+        history: &mut History,
+    ) -> Outcome {
+        if key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return Outcome::Unhandled;
+        }
+
+        if self.query.is_some() {
+            return self.handle_query(key_event.code, line, history);
+        }
+
+        match self.mode {
+            Mode::Insert => self.handle_insert(key_event, line),
+            Mode::Normal => self.handle_normal(key_event, line, history),
+        }
+    }
+
+    fn handle_insert(&mut self, key_event: KeyEvent, line: &mut Editor) -> Outcome {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                line.move_left();
+            }
+            KeyCode::Char(ch) => line.insert(ch),
+            KeyCode::Backspace => line.delete_backward(),
+            _ => return Outcome::Unhandled,
+        }
+
+        if self.recording && !self.replaying {
+            self.last_change.push(key_event);
+            self.recording = key_event.code != KeyCode::Esc;
+        }
+
+        Outcome::Handled
+    }
+
+    fn handle_normal(
+        &mut self,
+        key_event: KeyEvent,
+        line: &mut Editor,
+        history: &mut History,
+    ) -> Outcome {
+        let ch = match key_event.code {
+            KeyCode::Char(ch) => ch,
+            KeyCode::Esc => {
+                self.keys.clear();
+                return Outcome::Handled;
+            }
+            _ => return Outcome::Unhandled,
+        };
+
+        self.keys.push(ch);
+
+        let (count, command) = match parse(&self.keys) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => return Outcome::Handled,
+            Err(()) => {
+                self.keys.clear();
+                return Outcome::Bell;
+            }
+        };
+
+        let keys = std::mem::take(&mut self.keys);
+
+        if command.is_change() && !self.replaying {
+            self.last_change = keys
+                .chars()
+                .map(|ch| KeyEvent::from(KeyCode::Char(ch)))
+                .collect();
+            self.recording = true;
+        }
+
+        let outcome = self.run(command, count, line, history);
+
+        if self.mode == Mode::Normal {
+            self.recording = false;
+            clamp(line);
+        }
+
+        outcome
+    }
+
+    fn handle_query(&mut self, code: KeyCode, line: &mut Editor, history: &mut History) -> Outcome {
+        let Some(query) = self.query.as_mut() else {
+            return Outcome::Unhandled;
+        };
+
+        match code {
+            KeyCode::Char(ch) => query.push(ch),
+            KeyCode::Backspace if !query.is_empty() => {
+                query.pop();
+            }
+            KeyCode::Enter => {
+                let query = self.query.take().unwrap_or_default();
+                let before = history.entries().len();
+
+                return self.search(query, before, line, history);
+            }
+            _ => self.query = None,
+        }
+
+        Outcome::Handled
+    }
+
+    fn run(
+        &mut self,
+        command: Command,
+        count: usize,
+        line: &mut Editor,
+        history: &mut History,
+    ) -> Outcome {
+        match command {
+            Command::Move(motion) => match target(motion, line, count, false) {
+                Some(cursor) => line.move_to(cursor),
+                None => return Outcome::Bell,
+            },
+            // Like in vi, `cw` on a word only changes up to its end.
+            Command::Operate(Operator::Change, Motion::WordForward)
+                if line.text()[line.cursor()..].starts_with(|ch: char| !ch.is_whitespace()) =>
+            {
+                let rest = &line.text()[line.cursor()..];
+                let start = rest.chars().next().map_or(0, class);
+                let length = rest
+                    .find(|ch: char| class(ch) != start)
+                    .unwrap_or(rest.len());
+
+                self.operate(
+                    Operator::Change,
+                    line.cursor()..line.cursor() + length,
+                    line,
+                );
+            }
+            Command::Operate(operator, motion) => {
+                let Some(target) = target(motion, line, count, true) else {
+                    return Outcome::Bell;
+                };
+
+                let range = match target < line.cursor() {
+                    true => target..line.cursor(),
+                    false if is_inclusive(motion) => line.cursor()..next(line.text(), target),
+                    false => line.cursor()..target,
+                };
+
+                self.operate(operator, range, line);
+            }
+            Command::Line(Operator::Yank) => self.register = line.text().to_string(),
+            Command::Line(operator) => self.operate(operator, 0..line.text().len(), line),
+            Command::DeleteChar => {
+                if line.is_empty() {
+                    return Outcome::Bell;
+                }
+
+                let end = (0..count).fold(line.cursor(), |end, _| next(line.text(), end));
+                self.operate(Operator::Delete, line.cursor()..end, line);
+            }
+            Command::Put { after } => {
+                if self.register.is_empty() {
+                    return Outcome::Bell;
+                }
+
+                self.save(line);
+
+                let at = match after && !line.is_empty() {
+                    true => next(line.text(), line.cursor()),
+                    false => line.cursor(),
+                };
+                // At most `MAX_COUNT` bytes, unless the register alone is longer.
+                let copies = (MAX_COUNT / self.register.len()).min(count).max(1);
+                let text = self.register.repeat(copies);
+
+                line.replace(at..at, &text);
+                line.move_left();
+            }
+            Command::Insert(motion) => {
+                self.save(line);
+
+                if let Some(cursor) = motion.and_then(|motion| target(motion, line, 1, true)) {
+                    line.move_to(cursor);
+                }
+                self.mode = Mode::Insert;
+            }
+            Command::Undo => match self.undo.pop() {
+                Some((text, cursor)) => {
+                    line.set(&text);
+                    line.move_to(cursor);
+                }
+                None => return Outcome::Bell,
+            },
+            Command::Repeat => {
+                let keys = self.last_change.clone();
+
+                self.replaying = true;
+                for key in keys {
+                    self.handle(key, line, history);
+                }
+                self.replaying = false;
+
+                // A repeated insert ends in normal mode even when recording stopped early.
+                if self.mode == Mode::Insert {
+                    self.mode = Mode::Normal;
+                    line.move_left();
+                }
+            }
+            Command::Search => self.query = Some(String::new()),
+            Command::SearchAgain => match self.found.clone() {
+                Some((query, index)) => return self.search(query, index, line, history),
+                None => return Outcome::Bell,
+            },
+            Command::Older => match history.older(line.text()) {
+                Some(entry) => {
+                    line.set(entry);
+                    line.move_to(0);
+                }
+                None => return Outcome::Bell,
+            },
+            Command::Newer => match history.newer() {
+                Some(entry) => {
+                    line.set(entry);
+                    line.move_to(0);
+                }
+                None => return Outcome::Bell,
+            },
+        }
+
+        Outcome::Handled
+    }
+
+    fn operate(&mut self, operator: Operator, range: Range<usize>, line: &mut Editor) {
+        self.register = line.text()[range.clone()].to_string();
+
+        match operator {
+            Operator::Yank => line.move_to(range.start),
+            Operator::Delete => {
+                self.save(line);
+                line.replace(range, "");
+            }
+            Operator::Change => {
+                self.save(line);
+                line.replace(range, "");
+                self.mode = Mode::Insert;
+            }
+        }
+    }
+
+    /// Finds the newest history entry before `before` that contains `query`.
+    fn search(
+        &mut self,
+        query: String,
+        before: usize,
+        line: &mut Editor,
+        history: &History,
+    ) -> Outcome {
+        let Some(index) = history.search(&query, before) else {
+            return Outcome::Bell;
+        };
+
+        line.set(history.get(index).unwrap_or_default());
+        line.move_to(0);
+        self.found = Some((query, index));
+
+        Outcome::Handled
+    }
+
+    fn save(&mut self, line: &Editor) {
+        self.undo.push((line.text().to_string(), line.cursor()));
+    }
+}
+
+/// Parses the keys of a normal mode command: an optional count, then a motion, an operator
+/// with a motion or another command. `Ok(None)` while more keys are needed.
+fn parse(keys: &str) -> Result<Option<(usize, Command)>, ()> {
+    let (count, rest) = split_count(keys)?;
+    let mut chars = rest.chars();
+
+    let Some(ch) = chars.next() else {
+        return Ok(None);
+    };
+
+    let operator = match ch {
+        'd' => Operator::Delete,
+        'c' => Operator::Change,
+        'y' => Operator::Yank,
+        _ => {
+            let command = match ch {
+                'x' => Command::DeleteChar,
+                'p' => Command::Put { after: true },
+                'P' => Command::Put { after: false },
+                'i' => Command::Insert(None),
+                'a' => Command::Insert(Some(Motion::Right)),
+                'I' => Command::Insert(Some(Motion::Start)),
+                'A' => Command::Insert(Some(Motion::End)),
+                'D' => Command::Operate(Operator::Delete, Motion::End),
+                'C' => Command::Operate(Operator::Change, Motion::End),
+                'u' => Command::Undo,
+                '.' => Command::Repeat,
+                '/' => Command::Search,
+                'n' => Command::SearchAgain,
+                'k' => Command::Older,
+                'j' => Command::Newer,
+                _ => match parse_motion(rest)? {
+                    Some(motion) => Command::Move(motion),
+                    None => return Ok(None),
+                },
+            };
+
+            return Ok(Some((count, command)));
+        }
+    };
+
+    let (motion_count, motion_keys) = split_count(chars.as_str())?;
+
+    if motion_keys.starts_with(ch) {
+        return Ok(Some((count, Command::Line(operator))));
+    }
+
+    Ok(parse_motion(motion_keys)?.map(|motion| {
+        let count = count.saturating_mul(motion_count).min(MAX_COUNT);
+        (count, Command::Operate(operator, motion))
+    }))
+}
+
+fn parse_motion(keys: &str) -> Result<Option<Motion>, ()> {
+    let mut chars = keys.chars();
+
+    let motion = match chars.next() {
+        None => return Ok(None),
+        Some('h') => Motion::Left,
+        Some('l' | ' ') => Motion::Right,
+        Some('w') => Motion::WordForward,
+        Some('b') => Motion::WordBackward,
+        Some('e') => Motion::WordEnd,
+        Some('0') => Motion::Start,
+        Some('$') => Motion::End,
+        Some(find @ ('f' | 't' | 'F' | 'T')) => match chars.next() {
+            Some(ch) => Motion::Find {
+                ch,
+                forward: find.is_lowercase(),
+                till: find.eq_ignore_ascii_case(&'t'),
+            },
+            None => return Ok(None),
+        },
+        Some(_) => return Err(()),
+    };
+
+    Ok(Some(motion))
+}
+
+/// Splits a leading count off the keys, 1 without one. A `0` on its own is a motion. A count
+/// above `MAX_COUNT` is `MAX_COUNT`, one too big for a number is an error.
+fn split_count(keys: &str) -> Result<(usize, &str), ()> {
+    let digits = match keys.starts_with('0') {
+        true => 0,
+        false => keys.chars().take_while(char::is_ascii_digit).count(),
+    };
+    let count = match digits {
+        0 => 1,
+        _ => keys[..digits].parse::<usize>().map_err(|_| ())?,
+    };
+
+    Ok((count.min(MAX_COUNT), &keys[digits..]))
+}
+
+/// Where the motion takes the cursor, or `None` when it can't move. `operator` allows the
+/// end of the line, which normal mode keeps the cursor off.
+fn target(motion: Motion, line: &Editor, count: usize, operator: bool) -> Option<usize> {
+    let graphemes = unicode::graphemes(line.text());
+    let len = graphemes.len();
+    let at = graphemes
+        .iter()
+        .position(|&(index, _)| index == line.cursor())
+        .unwrap_or(len);
+    // A grapheme goes by its first character, the one the marks after it are on.
+    let class = |index: usize| graphemes[index].1.chars().next().map_or(0, class);
+    let last = match operator {
+        true => len,
+        false => len.saturating_sub(1),
+    };
+
+    let target = match motion {
+        Motion::Left => at.saturating_sub(count),
+        Motion::Right => (at + count).min(last),
+        Motion::Start => 0,
+        Motion::End => last,
+        Motion::WordForward => (0..count).fold(at, |mut index, _| {
+            if index < len && class(index) != 0 {
+                let start = class(index);
+                while index < len && class(index) == start {
+                    index += 1;
+                }
+            }
+            while index < len && class(index) == 0 {
+                index += 1;
+            }
+            index
+        }),
+        Motion::WordBackward => (0..count).fold(at, |mut index, _| {
+            while index > 0 && class(index - 1) == 0 {
+                index -= 1;
+            }
+            if index > 0 {
+                let start = class(index - 1);
+                while index > 0 && class(index - 1) == start {
+                    index -= 1;
+                }
+            }
+            index
+        }),
+        Motion::WordEnd => (0..count).fold(at, |mut index, _| {
+            index += 1;
+            while index < len && class(index) == 0 {
+                index += 1;
+            }
+            if index < len {
+                let start = class(index);
+                while index + 1 < len && class(index + 1) == start {
+                    index += 1;
+                }
+            }
+            index.min(len.saturating_sub(1))
+        }),
+        Motion::Find { ch, forward, till } => {
+            let mut index = at;
+
+            for _ in 0..count {
+                let found = match forward {
+                    true => (index + 1..len).find(|&i| graphemes[i].1.starts_with(ch)),
+                    false => (0..index).rev().find(|&i| graphemes[i].1.starts_with(ch)),
+                };
+                index = found?;
+            }
+
+            match (till, forward) {
+                (false, _) => index,
+                (true, true) => index - 1,
+                (true, false) => index + 1,
+            }
+        }
+    };
+
+    let moved = target != at || matches!(motion, Motion::Start | Motion::End);
+
+    moved.then(|| {
+        graphemes
+            .get(target)
+            .map_or(line.text().len(), |&(index, _)| index)
+    })
+}
+
+/// Motions whose target character is part of what an operator works on.
+fn is_inclusive(motion: Motion) -> bool {
+    matches!(
+        motion,
+        Motion::WordEnd | Motion::End | Motion::Find { forward: true, .. }
+    )
+}
+
+/// Whitespace, word characters and other characters, what `w`, `b` and `e` stop between.
+fn class(ch: char) -> u8 {
+    match ch {
+        ch if ch.is_whitespace() => 0,
+        ch if ch.is_alphanumeric() || ch == '_' => 1,
+        _ => 2,
+    }
+}
+
+fn next(text: &str, index: usize) -> usize {
+    unicode::next_boundary(text, index)
+}
+
+/// Keeps the cursor on a character in normal mode, never after the last one.
+fn clamp(line: &mut Editor) {
+    if !line.is_empty() && line.cursor() == line.text().len() {
+        line.move_left();
+    }
+}
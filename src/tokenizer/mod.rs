@@ -6,7 +6,7 @@ use std::{
 
 pub use token::Token;
 
-use crate::shell::core::ShellTokenizer;
+use crate::{parser, shell::core::ShellTokenizer};
 
 mod token;
 
@@ -28,25 +28,42 @@ impl ShellTokenizer<Token> for Tokenizer {
         let mut tokens: Vec<Token> = Vec::new();
         let mut buffer = String::new();
         let mut mode = ParseMode::None;
-        let mut sub_mode = ParseMode::None;
+        let mut quote_start = 0;
+        let mut heredocs: Vec<PendingHereDoc> = Vec::new();
 
         while let Some((i, ch)) = iter.next() {
             match mode {
                 ParseMode::None => match ch {
                     '\'' => mode = ParseMode::SingleQuote,
-                    '"' => mode = ParseMode::DoubleQuote,
-                    '\\' => {
-                        mode = ParseMode::Value;
-
-                        match iter.peek() {
-                            Some(_) => {
-                                let (_index, ch) = iter.next().unwrap();
+                    '"' => {
+                        mode = ParseMode::DoubleQuote;
+                        quote_start = tokens.len();
+                    }
+                    '$' => match parse_expansion(&mut iter, false)? {
+                        Some(token) => tokens.push(token),
+                        None => {
+                            mode = ParseMode::Value;
+                            buffer.push(ch);
+                        }
+                    },
+                    '`' => tokens.push(parse_backtick(&mut iter, false)?),
+                    '\\' => match iter.peek() {
+                        Some(&(_, next)) if is_glob_char(next) => {
+                            iter.next();
+                            tokens.push(Token::String(next.to_string(), false));
+                        }
+                        // A line continuation, the command goes on on the next line.
+                        Some(&(_, '\n')) => {
+                            iter.next();
+                        }
+                        Some(_) => {
+                            let (_index, ch) = iter.next().unwrap();
 
-                                buffer.push(ch)
-                            }
-                            None => todo!(),
+                            mode = ParseMode::Value;
+                            buffer.push(ch)
                         }
-                    }
+                        None => return Err(unfinished("Backslash at the end of the input.")),
+                    },
                     '-' => {
                         if matches!(iter.peek(), Some(&(_, '-'))) {
                             iter.next();
@@ -55,60 +72,156 @@ impl ShellTokenizer<Token> for Tokenizer {
                             mode = ParseMode::SingleDashArg
                         }
                     }
-                    'a'..='z' | 'A'..='Z' | '_' | '.' | '/' | '~' if buffer.is_empty() => {
-                        mode = ParseMode::Value;
-                        buffer.push(ch);
-                    }
                     '0'..='9' if buffer.is_empty() => {
+                        let fd = ch.to_digit(10).unwrap_or_default() as usize;
+
                         if let Some((_, '>')) = iter.peek() {
                             iter.next();
-                            tokens.push(parse_redirector(&mut iter, ch)?)
+                            tokens.push(parse_redirector(&mut iter, fd)?)
+                        } else if let Some((_, '<')) = iter.peek() {
+                            iter.next();
+                            parse_reader(&mut iter, fd, &mut tokens, &mut heredocs)?
                         } else {
                             buffer.push(ch);
                             mode = ParseMode::Value;
                         }
                     }
-                    '>' => tokens.push(parse_redirector(&mut iter, '1')?),
-                    ' ' => {
+                    '>' => tokens.push(parse_redirector(&mut iter, 1)?),
+                    '<' => parse_reader(&mut iter, 0, &mut tokens, &mut heredocs)?,
+                    '|' | '&' | ';' => tokens.push(parse_operator(&mut iter, ch, i)?),
+                    ' ' | '\t' => {
                         if tokens.last() != Some(&Token::Space) {
                             tokens.push(Token::Space)
                         }
                     }
+                    '(' => tokens.push(Token::OpenParen),
+                    ')' => tokens.push(Token::CloseParen),
+                    '\n' => parse_newline(&mut iter, &mut tokens, &mut heredocs)?,
+                    // A comment runs to the end of the line.
+                    '#' => {
+                        while matches!(iter.peek(), Some(&(_, next)) if next != '\n') {
+                            iter.next();
+                        }
+                    }
+                    // Anything but a metacharacter starts a word.
                     _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid character at {}", i),
-                        ))
+                        mode = ParseMode::Value;
+                        buffer.push(ch);
                     }
                 },
                 ParseMode::Value => match ch {
-                    'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' | '/' => buffer.push(ch),
-                    '\\' => {
-                        let ch = iter.peek();
-
-                        match ch {
-                            Some(_) => {
-                                let (_index, ch) = iter.next().unwrap();
+                    '$' => match parse_expansion(&mut iter, false)? {
+                        Some(token) => {
+                            tokens.push(generate_token(mode, &buffer));
+                            tokens.push(token);
 
-                                buffer.push(ch)
-                            }
-                            None => todo!(),
+                            buffer = String::new();
+                            mode = ParseMode::None;
                         }
+                        None => buffer.push(ch),
+                    },
+                    '`' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_backtick(&mut iter, false)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
                     }
-                    ' ' => {
+                    '\\' => match iter.peek() {
+                        Some(&(_, next)) if is_glob_char(next) => {
+                            iter.next();
+                            tokens.push(generate_token(mode, &buffer));
+                            tokens.push(Token::String(next.to_string(), false));
+
+                            buffer = String::new();
+                            mode = ParseMode::None;
+                        }
+                        Some(&(_, '\n')) => {
+                            iter.next();
+                        }
+                        Some(_) => {
+                            let (_index, ch) = iter.next().unwrap();
+
+                            buffer.push(ch)
+                        }
+                        None => return Err(unfinished("Backslash at the end of the input.")),
+                    },
+                    ' ' | '\t' => {
                         tokens.push(generate_token(mode, &buffer));
                         tokens.push(Token::Space);
 
                         buffer = String::new();
                         mode = ParseMode::None;
-                        sub_mode = ParseMode::None;
                     }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid character at {}", i),
-                        ))
+                    '|' | '&' | ';' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_operator(&mut iter, ch, i)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    // A number right before the operator is the descriptor it redirects.
+                    '>' | '<'
+                        if !buffer.is_empty() && buffer.chars().all(|c| c.is_ascii_digit()) =>
+                    {
+                        let fd = buffer.parse().map_err(|_| {
+                            Error::new(
+                                ErrorKind::InvalidInput,
+                                format!("{}: Bad file descriptor", buffer),
+                            )
+                        })?;
+
+                        match ch {
+                            '>' => tokens.push(parse_redirector(&mut iter, fd)?),
+                            _ => parse_reader(&mut iter, fd, &mut tokens, &mut heredocs)?,
+                        }
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '>' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_redirector(&mut iter, 1)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '<' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        parse_reader(&mut iter, 0, &mut tokens, &mut heredocs)?;
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '(' | ')' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(match ch {
+                            '(' => Token::OpenParen,
+                            _ => Token::CloseParen,
+                        });
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '\n' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        parse_newline(&mut iter, &mut tokens, &mut heredocs)?;
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '\'' | '"' => {
+                        tokens.push(generate_token(mode, &buffer));
+
+                        buffer = String::new();
+                        mode = match ch {
+                            '"' => ParseMode::DoubleQuote,
+                            _ => ParseMode::SingleQuote,
+                        };
+                        quote_start = tokens.len();
                     }
+                    // Anything but a metacharacter is part of the word.
+                    _ => buffer.push(ch),
                 },
                 ParseMode::SingleQuote => match ch {
                     '\'' => {
@@ -116,107 +229,666 @@ impl ShellTokenizer<Token> for Tokenizer {
 
                         buffer = String::new();
                         mode = ParseMode::None;
-                        sub_mode = ParseMode::None;
                     }
                     _ => buffer.push(ch),
                 },
                 ParseMode::DoubleQuote => match ch {
                     '"' => {
-                        tokens.push(generate_token(mode, &buffer));
+                        if !buffer.is_empty() || tokens.len() == quote_start {
+                            tokens.push(generate_token(mode, &buffer));
+                        }
 
                         buffer = String::new();
                         mode = ParseMode::None;
-                        sub_mode = ParseMode::None;
                     }
-                    '\\' => {
-                        if sub_mode == ParseMode::SingleQuote {
-                            buffer.push(ch);
-                            continue;
-                        }
-
-                        match iter.peek() {
-                            Some((_, '\\' | '$' | '"')) => {
-                                let (_index, ch) = iter.next().unwrap();
+                    '\\' => match iter.peek() {
+                        Some((_, '\\' | '$' | '"')) => {
+                            let (_index, ch) = iter.next().unwrap();
 
-                                buffer.push(ch)
-                            }
-                            Some(_) => {
-                                buffer.push(ch);
-                            }
-                            None => todo!(),
+                            buffer.push(ch)
                         }
-                    }
-                    '\'' => match sub_mode {
-                        ParseMode::None => {
-                            buffer.push(ch);
-                            sub_mode = ParseMode::SingleQuote;
+                        Some((_, '\n')) => {
+                            iter.next();
                         }
-                        ParseMode::SingleQuote => {
+                        Some(_) => {
                             buffer.push(ch);
-                            sub_mode = ParseMode::None;
                         }
-                        _ => todo!(),
+                        None => return Err(unfinished("Double quote didn't end.")),
+                    },
+                    '$' => match parse_expansion(&mut iter, true)? {
+                        Some(token) => {
+                            if !buffer.is_empty() {
+                                tokens.push(generate_token(ParseMode::DoubleQuote, &buffer));
+                                buffer = String::new();
+                            }
+
+                            tokens.push(token);
+                        }
+                        None => buffer.push(ch),
                     },
+                    '`' => {
+                        if !buffer.is_empty() {
+                            tokens.push(generate_token(ParseMode::DoubleQuote, &buffer));
+                            buffer = String::new();
+                        }
+
+                        tokens.push(parse_backtick(&mut iter, true)?);
+                    }
                     _ => buffer.push(ch),
                 },
                 ParseMode::SingleDashArg | ParseMode::DoubleDashArg => match ch {
-                    'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => buffer.push(ch),
-                    ' ' => {
+                    '$' => match parse_expansion(&mut iter, false)? {
+                        Some(token) => {
+                            tokens.push(generate_token(mode, &buffer));
+                            tokens.push(token);
+
+                            buffer = String::new();
+                            mode = ParseMode::None;
+                        }
+                        None => buffer.push(ch),
+                    },
+                    '`' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_backtick(&mut iter, false)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '\\' => match iter.peek() {
+                        Some(&(_, next)) if is_glob_char(next) => {
+                            iter.next();
+                            tokens.push(generate_token(mode, &buffer));
+                            tokens.push(Token::String(next.to_string(), false));
+
+                            buffer = String::new();
+                            mode = ParseMode::None;
+                        }
+                        Some(&(_, '\n')) => {
+                            iter.next();
+                        }
+                        Some(_) => {
+                            let (_index, ch) = iter.next().unwrap();
+
+                            buffer.push(ch)
+                        }
+                        None => return Err(unfinished("Backslash at the end of the input.")),
+                    },
+                    ' ' | '\t' => {
                         tokens.push(generate_token(mode, &buffer));
                         tokens.push(Token::Space);
 
                         buffer = String::new();
                         mode = ParseMode::None;
-                        sub_mode = ParseMode::None;
                     }
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid character at {}", i),
-                        ))
+                    '|' | '&' | ';' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_operator(&mut iter, ch, i)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '>' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(parse_redirector(&mut iter, 1)?);
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '<' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        parse_reader(&mut iter, 0, &mut tokens, &mut heredocs)?;
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '(' | ')' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        tokens.push(match ch {
+                            '(' => Token::OpenParen,
+                            _ => Token::CloseParen,
+                        });
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '\n' => {
+                        tokens.push(generate_token(mode, &buffer));
+                        parse_newline(&mut iter, &mut tokens, &mut heredocs)?;
+
+                        buffer = String::new();
+                        mode = ParseMode::None;
+                    }
+                    '\'' | '"' => {
+                        tokens.push(generate_token(mode, &buffer));
+
+                        buffer = String::new();
+                        mode = match ch {
+                            '"' => ParseMode::DoubleQuote,
+                            _ => ParseMode::SingleQuote,
+                        };
+                        quote_start = tokens.len();
                     }
+                    _ => buffer.push(ch),
                 },
             }
         }
 
         match mode {
-            ParseMode::SingleQuote => {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "Single quote didn't end.",
-                ))
+            ParseMode::SingleQuote => return Err(unfinished("Single quote didn't end.")),
+            ParseMode::DoubleQuote => return Err(unfinished("Double quote didn't end.")),
+            ParseMode::None => {}
+            _ => tokens.push(generate_token(mode, &buffer)),
+        }
+
+        if let Some(heredoc) = heredocs.first() {
+            return Err(unfinished_heredoc(&heredoc.delimiter));
+        }
+
+        return Ok(tokens);
+    }
+}
+
+impl Tokenizer {
+    /// Splits the body of an unquoted here-document into its literal text and expansions.
+    /// Only `\$`, `` \` `` and `\\` are escapes, quotes are ordinary characters.
+    pub fn tokenize_heredoc(body: &str) -> Result<Vec<Token>, Error> {
+        let mut iter = body.chars().enumerate().peekable();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut buffer = String::new();
+
+        while let Some((_, ch)) = iter.next() {
+            let token = match ch {
+                '\\' => {
+                    match iter.peek() {
+                        Some(&(_, next @ ('$' | '`' | '\\'))) => {
+                            iter.next();
+                            buffer.push(next);
+                        }
+                        _ => buffer.push(ch),
+                    }
+                    continue;
+                }
+                '$' => match parse_expansion(&mut iter, true)? {
+                    Some(token) => token,
+                    None => {
+                        buffer.push(ch);
+                        continue;
+                    }
+                },
+                '`' => parse_backtick(&mut iter, true)?,
+                ch => {
+                    buffer.push(ch);
+                    continue;
+                }
+            };
+
+            if !buffer.is_empty() {
+                tokens.push(Token::String(std::mem::take(&mut buffer), true));
             }
-            ParseMode::DoubleQuote => {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "Double quote didn't end.",
-                ))
+            tokens.push(token);
+        }
+
+        if !buffer.is_empty() {
+            tokens.push(Token::String(buffer, true));
+        }
+
+        Ok(tokens)
+    }
+
+    /// Splits the word of a `${NAME:-word}` and the like into its literal text and expansions.
+    /// Quotes and backslashes are removed, other characters are literal, blanks included.
+    pub fn tokenize_word(word: &str) -> Result<Vec<Token>, Error> {
+        let mut iter = word.chars().enumerate().peekable();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut buffer = String::new();
+        let mut in_double_quote = false;
+
+        while let Some((_, ch)) = iter.next() {
+            let token = match ch {
+                '\\' => {
+                    match iter.next() {
+                        Some((_, next))
+                            if !in_double_quote || matches!(next, '$' | '`' | '"' | '\\') =>
+                        {
+                            buffer.push(next)
+                        }
+                        Some((_, next)) => {
+                            buffer.push(ch);
+                            buffer.push(next);
+                        }
+                        None => buffer.push(ch),
+                    }
+                    continue;
+                }
+                '\'' if !in_double_quote => {
+                    for (_, next) in iter.by_ref() {
+                        if next == '\'' {
+                            break;
+                        }
+                        buffer.push(next);
+                    }
+                    continue;
+                }
+                '"' => {
+                    in_double_quote = !in_double_quote;
+                    continue;
+                }
+                '$' => match parse_expansion(&mut iter, true)? {
+                    Some(token) => token,
+                    None => {
+                        buffer.push(ch);
+                        continue;
+                    }
+                },
+                '`' => parse_backtick(&mut iter, true)?,
+                ch => {
+                    buffer.push(ch);
+                    continue;
+                }
+            };
+
+            if !buffer.is_empty() {
+                tokens.push(Token::String(std::mem::take(&mut buffer), true));
             }
-            ParseMode::None => {
-                return Ok(tokens.to_vec());
+            tokens.push(token);
+        }
+
+        if !buffer.is_empty() {
+            tokens.push(Token::String(buffer, true));
+        }
+
+        Ok(tokens)
+    }
+}
+
+/// A here-document whose body starts on the next line.
+struct PendingHereDoc {
+    index: usize,
+    delimiter: String,
+    strip_tabs: bool,
+}
+
+/// Parses what follows a `>`: `>>` appends, `>|` overrides noclobber and `>&` duplicates.
+fn parse_redirector(
+    iter: &mut Peekable<Enumerate<Chars<'_>>>,
+    prefix: usize,
+) -> Result<Token, Error> {
+    let token = match iter.peek() {
+        Some((_, '>')) => Token::Appender(prefix),
+        Some((_, '|')) => Token::Clobber(prefix),
+        Some((_, '&')) => Token::Duplicator(prefix),
+        Some(_) => return Ok(Token::Redirector(prefix)),
+        None => return Err(Error::new(ErrorKind::InvalidInput, "No redirection target")),
+    };
+
+    iter.next();
+    Ok(token)
+}
+
+/// Parses what follows a `<`: an input redirection, `<&` duplication, `<>` read-write, a `<<`
+/// or `<<-` here-document or a `<<<` here-string. A here-document's body is filled in once its
+/// line ends.
+fn parse_reader(
+    iter: &mut Peekable<Enumerate<Chars<'_>>>,
+    prefix: usize,
+    tokens: &mut Vec<Token>,
+    heredocs: &mut Vec<PendingHereDoc>,
+) -> Result<(), Error> {
+    match iter.peek() {
+        Some(&(_, '<')) => iter.next(),
+        Some(&(_, '&')) => {
+            iter.next();
+            tokens.push(Token::InputDuplicator(prefix));
+            return Ok(());
+        }
+        Some(&(_, '>')) => {
+            iter.next();
+            tokens.push(Token::ReadWriter(prefix));
+            return Ok(());
+        }
+        _ => {
+            tokens.push(Token::Reader(prefix));
+            return Ok(());
+        }
+    };
+
+    let strip_tabs = match iter.peek() {
+        Some(&(_, '<')) => {
+            iter.next();
+            tokens.push(Token::HereString);
+            return Ok(());
+        }
+        Some(&(_, '-')) => {
+            iter.next();
+            true
+        }
+        _ => false,
+    };
+
+    while let Some(&(_, ' ' | '\t')) = iter.peek() {
+        iter.next();
+    }
+
+    let (delimiter, quoted) = parse_delimiter(iter)?;
+
+    heredocs.push(PendingHereDoc {
+        index: tokens.len(),
+        delimiter,
+        strip_tabs,
+    });
+    tokens.push(Token::HereDoc(String::new(), !quoted));
+
+    Ok(())
+}
+
+/// Reads a here-document delimiter. Any quoting in it turns expansion off for the body.
+fn parse_delimiter(iter: &mut Peekable<Enumerate<Chars<'_>>>) -> Result<(String, bool), Error> {
+    let mut delimiter = String::new();
+    let mut quoted = false;
+
+    while let Some(&(_, ch)) = iter.peek() {
+        match ch {
+            ' ' | '\t' | '\n' | '|' | '&' | ';' | '<' | '>' | '(' | ')' => break,
+            '\'' | '"' => {
+                iter.next();
+                quoted = true;
+
+                for (_, next) in iter.by_ref() {
+                    if next == ch {
+                        break;
+                    }
+                    delimiter.push(next);
+                }
+            }
+            '\\' => {
+                iter.next();
+                quoted = true;
+                delimiter.extend(iter.next().map(|(_, next)| next));
             }
-            _ => {
-                tokens.push(generate_token(mode, &buffer));
-                return Ok(tokens.to_vec());
+            ch => {
+                iter.next();
+                delimiter.push(ch);
             }
         }
     }
+
+    if delimiter.is_empty() && !quoted {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "syntax error near unexpected token `newline'",
+        ));
+    }
+
+    Ok((delimiter, quoted))
 }
 
-fn parse_redirector(
+/// Reads the bodies of the here-documents started on the line that just ended, then ends the
+/// command like a `;` would.
+fn parse_newline(
+    iter: &mut Peekable<Enumerate<Chars<'_>>>,
+    tokens: &mut Vec<Token>,
+    heredocs: &mut Vec<PendingHereDoc>,
+) -> Result<(), Error> {
+    for heredoc in heredocs.drain(..) {
+        let body = read_heredoc(iter, &heredoc)?;
+
+        if let Some(Token::HereDoc(content, _)) = tokens.get_mut(heredoc.index) {
+            *content = body;
+        }
+    }
+
+    let ends_command = matches!(
+        tokens.iter().rev().find(|t| t != &&Token::Space),
+        Some(token) if !token.is_list_operator() && token != &Token::Pipe
+    );
+
+    if ends_command {
+        tokens.push(Token::Semicolon);
+    }
+
+    Ok(())
+}
+
+fn read_heredoc(
     iter: &mut Peekable<Enumerate<Chars<'_>>>,
-    prefix: char,
+    heredoc: &PendingHereDoc,
+) -> Result<String, Error> {
+    let mut body = String::new();
+
+    loop {
+        let mut line = String::new();
+        let mut ended = false;
+
+        for (_, ch) in iter.by_ref() {
+            if ch == '\n' {
+                ended = true;
+                break;
+            }
+            line.push(ch);
+        }
+
+        let line = match heredoc.strip_tabs {
+            true => line.trim_start_matches('\t'),
+            false => line.as_str(),
+        };
+
+        if line == heredoc.delimiter {
+            return Ok(body);
+        }
+
+        if !ended {
+            return Err(unfinished_heredoc(&heredoc.delimiter));
+        }
+
+        body.push_str(line);
+        body.push('\n');
+    }
+}
+
+/// Reported when the input ends in the middle of a word, like in an open quote, or after a
+/// backslash. The caller can ask for more lines.
+fn unfinished(message: &str) -> Error {
+    Error::new(ErrorKind::UnexpectedEof, message)
+}
+
+/// Reported when the input ends inside a here-document, the caller can ask for more lines.
+fn unfinished_heredoc(delimiter: &str) -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        format!(
+            "here-document delimited by end-of-file (wanted `{}')",
+            delimiter
+        ),
+    )
+}
+
+fn parse_operator(
+    iter: &mut Peekable<Enumerate<Chars<'_>>>,
+    ch: char,
+    index: usize,
 ) -> Result<Token, Error> {
+    let doubled = matches!(iter.peek(), Some(&(_, next)) if next == ch);
+
+    match (ch, doubled) {
+        ('|', true) => {
+            iter.next();
+            Ok(Token::Or)
+        }
+        ('|', false) => Ok(Token::Pipe),
+        ('&', true) => {
+            iter.next();
+            Ok(Token::And)
+        }
+        ('&', false) if matches!(iter.peek(), Some(&(_, '>'))) => {
+            iter.next();
+
+            match iter.peek() {
+                Some(&(_, '>')) => {
+                    iter.next();
+                    Ok(Token::BothAppender)
+                }
+                _ => Ok(Token::BothRedirector),
+            }
+        }
+        ('&', false) => Ok(Token::Background),
+        (';', true) => {
+            iter.next();
+            Ok(Token::DoubleSemicolon)
+        }
+        (';', false) => Ok(Token::Semicolon),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid character at {}", index),
+        )),
+    }
+}
+
+/// Reads a parameter expansion or a `$(...)` command substitution after a `$`.
+/// Returns `None` when the `$` doesn't start an expansion.
+fn parse_expansion(
+    iter: &mut Peekable<Enumerate<Chars<'_>>>,
+    quoted: bool,
+) -> Result<Option<Token>, Error> {
+    if let Some(&(_, '(')) = iter.peek() {
+        iter.next();
+        return Ok(Some(Token::Substitution(parse_substitution(iter)?, quoted)));
+    }
+
+    Ok(parse_variable(iter)?.map(|expression| Token::Variable(expression, quoted)))
+}
+
+/// Reads the command of a `$(...)` up to its closing parenthesis. Parentheses inside quotes
+/// don't count, nor one that leaves the command unfinished, like the `)` after a pattern of
+/// a `case`.
+fn parse_substitution(iter: &mut Peekable<Enumerate<Chars<'_>>>) -> Result<String, Error> {
+    let mut command = String::new();
+    let mut depth = 0;
+
+    while let Some((_, ch)) = iter.next() {
+        match ch {
+            ')' if depth == 0 && !is_unfinished(&command) => return Ok(command),
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            '\\' => {
+                command.push(ch);
+                command.extend(iter.next().map(|(_, ch)| ch));
+                continue;
+            }
+            '\'' => {
+                command.push(ch);
+
+                for (_, ch) in iter.by_ref() {
+                    command.push(ch);
+
+                    if ch == '\'' {
+                        break;
+                    }
+                }
+                continue;
+            }
+            '"' => {
+                command.push(ch);
+
+                while let Some((_, ch)) = iter.next() {
+                    command.push(ch);
+
+                    match ch {
+                        '\\' => command.extend(iter.next().map(|(_, ch)| ch)),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        command.push(ch);
+    }
+
+    Err(unfinished("Command substitution didn't end."))
+}
+
+/// Whether `command` only parses with more input after it.
+fn is_unfinished(command: &str) -> bool {
+    let parsed = Tokenizer::tokenize(command).and_then(|tokens| parser::parse(&tokens));
+
+    matches!(parsed, Err(err) if err.kind() == ErrorKind::UnexpectedEof)
+}
+
+/// Reads a legacy `` `...` `` command substitution, where a backslash escapes `` ` ``, `\` and `$`.
+fn parse_backtick(iter: &mut Peekable<Enumerate<Chars<'_>>>, quoted: bool) -> Result<Token, Error> {
+    let mut command = String::new();
+
+    while let Some((_, ch)) = iter.next() {
+        match ch {
+            '`' => return Ok(Token::Substitution(command, quoted)),
+            '\\' => match iter.peek() {
+                Some(&(_, next @ ('`' | '\\' | '$'))) => {
+                    iter.next();
+                    command.push(next);
+                }
+                _ => command.push(ch),
+            },
+            ch => command.push(ch),
+        }
+    }
+
+    Err(unfinished("Command substitution didn't end."))
+}
+
+/// Reads the expression after a `$`. Returns `None` when the `$` doesn't start an expansion.
+fn parse_variable(iter: &mut Peekable<Enumerate<Chars<'_>>>) -> Result<Option<String>, Error> {
+    let mut expression = String::new();
+
     match iter.peek() {
-        Some((_, '>')) => {
+        Some(&(_, '{')) => {
             iter.next();
-            Ok(Token::Appender(prefix))
+            let mut depth = 0;
+
+            loop {
+                match iter.next() {
+                    Some((_, '}')) if depth == 0 => return Ok(Some(expression)),
+                    Some((_, ch)) => {
+                        match ch {
+                            '{' => depth += 1,
+                            '}' => depth -= 1,
+                            _ => {}
+                        }
+                        expression.push(ch);
+                    }
+                    None => return Err(unfinished("Parameter expansion didn't end.")),
+                }
+            }
         }
-        Some(_) => Ok(Token::Redirector(prefix)),
-        None => return Err(Error::new(ErrorKind::InvalidInput, "No redirection target")),
+        Some(&(_, ch)) if ch.is_ascii_alphabetic() || ch == '_' => {
+            while let Some(&(_, ch)) = iter.peek() {
+                if !ch.is_ascii_alphanumeric() && ch != '_' {
+                    break;
+                }
+
+                expression.push(ch);
+                iter.next();
+            }
+
+            Ok(Some(expression))
+        }
+        Some(&(_, ch))
+            if ch.is_ascii_digit() || matches!(ch, '?' | '$' | '#' | '@' | '*' | '!' | '-') =>
+        {
+            iter.next();
+            Ok(Some(ch.to_string()))
+        }
+        _ => Ok(None),
     }
 }
 
+/// Characters with a meaning in pathname expansion. An escaped one becomes its own quoted token
+/// so it stays literal when the word is globbed.
+fn is_glob_char(ch: char) -> bool {
+    matches!(ch, '*' | '?' | '[' | ']')
+}
+
 fn generate_token(mode: ParseMode, value: &str) -> Token {
     match mode {
         ParseMode::None => panic!("Tried to push a token before it started to parse anything"),
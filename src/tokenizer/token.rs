@@ -1,16 +1,32 @@
-use std::{
-    fmt::Display,
-    io::{Error, ErrorKind},
-};
+use std::fmt::Display;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Token {
     Space,
     Value(String),
     Argument(String, bool),
     String(String, bool),
-    Redirector(char),
-    Appender(char),
+    Variable(String, bool),
+    Substitution(String, bool),
+    Redirector(usize),
+    Appender(usize),
+    Clobber(usize),
+    Duplicator(usize),
+    BothRedirector,
+    BothAppender,
+    Reader(usize),
+    InputDuplicator(usize),
+    ReadWriter(usize),
+    HereDoc(String, bool),
+    HereString,
+    Pipe,
+    And,
+    Or,
+    Semicolon,
+    Background,
+    DoubleSemicolon,
+    OpenParen,
+    CloseParen,
 }
 
 impl Token {
@@ -23,65 +39,79 @@ impl Token {
                 format!("{}{}", dashes, val)
             }
             Token::String(val, _) => val.to_string(),
+            Token::Variable(expression, _) => format!("${{{}}}", expression),
+            Token::Substitution(command, _) => format!("$({})", command),
             Token::Redirector(num) => format!("{}>", num),
             Token::Appender(num) => format!("{}>>", num),
+            Token::Clobber(num) => format!("{}>|", num),
+            Token::Duplicator(num) => format!("{}>&", num),
+            Token::BothRedirector => String::from("&>"),
+            Token::BothAppender => String::from("&>>"),
+            Token::Reader(num) => format!("{}<", num),
+            Token::InputDuplicator(num) => format!("{}<&", num),
+            Token::ReadWriter(num) => format!("{}<>", num),
+            Token::HereDoc(_, _) => String::from("<<"),
+            Token::HereString => String::from("<<<"),
+            Token::Pipe => String::from("|"),
+            Token::And => String::from("&&"),
+            Token::Or => String::from("||"),
+            Token::Semicolon => String::from(";"),
+            Token::Background => String::from("&"),
+            Token::DoubleSemicolon => String::from(";;"),
+            Token::OpenParen => String::from("("),
+            Token::CloseParen => String::from(")"),
         }
     }
 
     pub fn is_redirection_token(&self) -> bool {
-        match self {
-            Token::Space => false,
-            Token::Value(_) => false,
-            Token::Argument(_, _) => false,
-            Token::String(_, _) => false,
-            Token::Redirector(_) => true,
-            Token::Appender(_) => true,
-        }
+        matches!(
+            self,
+            Token::Redirector(_)
+                | Token::Appender(_)
+                | Token::Clobber(_)
+                | Token::Duplicator(_)
+                | Token::BothRedirector
+                | Token::BothAppender
+                | Token::Reader(_)
+                | Token::InputDuplicator(_)
+                | Token::ReadWriter(_)
+                | Token::HereDoc(_, _)
+                | Token::HereString
+        )
     }
 
-    pub fn is_redirection_ok(&self) -> Result<bool, Error> {
-        match self {
-            Token::Space => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Value(_) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Argument(_, _) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::String(_, _) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Redirector(prefix) => Ok(prefix == &'1'),
-            Token::Appender(prefix) => Ok(prefix == &'1'),
-        }
+    /// Tokens that are part of a word. Adjacent word tokens without a space between them form one argument.
+    pub fn is_word_token(&self) -> bool {
+        matches!(
+            self,
+            Token::Value(_)
+                | Token::Argument(_, _)
+                | Token::String(_, _)
+                | Token::Variable(_, _)
+                | Token::Substitution(_, _)
+        )
+    }
+
+    pub fn is_list_operator(&self) -> bool {
+        matches!(
+            self,
+            Token::And | Token::Or | Token::Semicolon | Token::Background | Token::DoubleSemicolon
+        )
     }
 
-    pub fn is_redirection_err(&self) -> Result<bool, Error> {
+    /// The file descriptor a redirection applies to, `None` for `&>` and `&>>` which redirect
+    /// both stdout and stderr.
+    pub fn redirection_fd(&self) -> Option<usize> {
         match self {
-            Token::Space => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Value(_) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Argument(_, _) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::String(_, _) => Err(Error::new(
-                ErrorKind::Other,
-                "Checked token is not a redirection token",
-            )),
-            Token::Redirector(prefix) => Ok(prefix == &'2'),
-            Token::Appender(prefix) => Ok(prefix == &'2'),
+            Token::Redirector(prefix)
+            | Token::Appender(prefix)
+            | Token::Clobber(prefix)
+            | Token::Duplicator(prefix)
+            | Token::Reader(prefix)
+            | Token::InputDuplicator(prefix)
+            | Token::ReadWriter(prefix) => Some(*prefix),
+            Token::HereDoc(_, _) | Token::HereString => Some(0),
+            _ => None,
         }
     }
 }
@@ -91,16 +121,3 @@ impl Display for Token {
         write!(f, "{}", self.serialize())
     }
 }
-
-impl Clone for Token {
-    fn clone(&self) -> Self {
-        match self {
-            Self::Space => Self::Space,
-            Self::Value(arg0) => Self::Value(arg0.clone()),
-            Self::Argument(arg0, arg1) => Self::Argument(arg0.clone(), *arg1),
-            Self::String(arg0, arg1) => Self::String(arg0.clone(), *arg1),
-            Self::Redirector(arg0) => Self::Redirector(*arg0),
-            Self::Appender(arg0) => Self::Appender(*arg0),
-        }
-    }
-}
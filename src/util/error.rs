@@ -1,4 +1,4 @@
-use std::io::Error;
+use std::{fmt::Display, io::Error};
 
 pub trait AsBytes {
     fn as_bytes(&self) -> Vec<u8>;
@@ -9,3 +9,42 @@ impl AsBytes for Error {
         self.to_string().as_bytes().to_vec()
     }
 }
+
+/// A builtin's exit status other than the usual 0 or 1, carried as an error without a message.
+#[derive(Debug)]
+pub struct Status(pub i32);
+
+impl Display for Status {
+    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl std::error::Error for Status {}
+
+/// Turns an exit status into the result of a builtin, `Ok` when it's 0.
+pub fn status_result(status: i32) -> Result<String, Error> {
+    match status {
+        0 => Ok(String::new()),
+        status => Err(Error::other(Status(status))),
+    }
+}
+
+/// An error that gives up on the rest of the input, like `${NAME:?}` with `NAME` unset or a
+/// foreground job that stopped. A script or `-c` ends on it, the interactive shell goes back
+/// to the prompt. The status is already set.
+#[derive(Debug)]
+pub struct Abort(pub String);
+
+impl Display for Abort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Abort {}
+
+/// Whether the error is an `Abort`.
+pub fn is_abort(err: &Error) -> bool {
+    err.get_ref().is_some_and(|e| e.is::<Abort>())
+}
@@ -0,0 +1,111 @@
+use std::fs;
+
+use super::pattern;
+
+/// Expands a pathname pattern into the sorted list of matching paths.
+/// With `globstar`, a `**` component matches any number of directories.
+pub fn expand(pattern: &str, globstar: bool) -> Vec<String> {
+    let (mut paths, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (vec![String::from("/")], rest),
+        None => (vec![String::new()], pattern),
+    };
+
+    let components: Vec<&str> = rest.split('/').filter(|c| !c.is_empty()).collect();
+    let last = components.len().saturating_sub(1);
+
+    for (index, component) in components.iter().enumerate() {
+        let is_last = index == last;
+
+        paths = paths
+            .iter()
+            .flat_map(|base| match *component {
+                "**" if globstar => descendants(base, is_last),
+                component if pattern::has_wildcards(component) => {
+                    children(base, component, is_last)
+                }
+                component => vec![join(base, &pattern::unescape(component))],
+            })
+            .collect();
+    }
+
+    if pattern.ends_with('/') {
+        paths.retain(|path| is_dir(path));
+        paths.iter_mut().for_each(|path| path.push('/'));
+    }
+
+    paths.retain(|path| !path.is_empty() && fs::symlink_metadata(path).is_ok());
+    paths.sort();
+    paths.dedup();
+
+    paths
+}
+
+/// Entries of `base` matching `component`. Hidden entries only match a pattern starting
+/// with a `.`, and only directories are kept when more components follow.
+fn children(base: &str, component: &str, is_last: bool) -> Vec<String> {
+    let show_hidden = component.starts_with('.') || component.starts_with("\\.");
+
+    entries(base)
+        .into_iter()
+        .filter(|name| show_hidden || !name.starts_with('.'))
+        .filter(|name| pattern::matches(component, name))
+        .map(|name| join(base, &name))
+        .filter(|path| is_last || is_dir(path))
+        .collect()
+}
+
+/// `base` itself and every directory below it, or every entry below it when `**` is the
+/// last component.
+fn descendants(base: &str, is_last: bool) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut pending = vec![base.to_string()];
+
+    if !is_last {
+        found.push(base.to_string());
+    }
+
+    while let Some(dir) = pending.pop() {
+        for name in entries(&dir) {
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let path = join(&dir, &name);
+
+            if is_dir(&path) {
+                pending.push(path.clone());
+                found.push(path);
+            } else if is_last {
+                found.push(path);
+            }
+        }
+    }
+
+    found
+}
+
+fn entries(dir: &str) -> Vec<String> {
+    let dir = if dir.is_empty() { "." } else { dir };
+
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn join(base: &str, name: &str) -> String {
+    match base {
+        "" => name.to_string(),
+        base if base.ends_with('/') => format!("{}{}", base, name),
+        base => format!("{}/{}", base, name),
+    }
+}
+
+fn is_dir(path: &str) -> bool {
+    let path = if path.is_empty() { "." } else { path };
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_dir())
+}
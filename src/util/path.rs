@@ -9,7 +9,8 @@ pub trait ExecutionPath {
 
 impl ExecutionPath for &String {
     fn get_exec_path(&self) -> Option<PathBuf> {
-        for path in env::var("PATH").unwrap().split(":") {
+        // A missing PATH, after `unset PATH`, counts as an empty one.
+        for path in env::var("PATH").unwrap_or_default().split(":") {
             let cmd_path = Path::new(path).join(self);
 
             if cmd_path.exists() {
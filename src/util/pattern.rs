@@ -0,0 +1,163 @@
+/// Matches `text` against a shell pattern with `*`, `?`, `[...]` and `\` escapes.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    match_from(&pattern, &text)
+}
+
+/// Whether the pattern has any unescaped special characters.
+pub fn has_wildcards(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Escapes every special character so the text only matches itself.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        if matches!(ch, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+/// Removes the escapes from a pattern without wildcards.
+pub fn unescape(pattern: &str) -> String {
+    let mut unescaped = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.extend(chars.next()),
+            ch => unescaped.push(ch),
+        }
+    }
+
+    unescaped
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return text.is_empty();
+    };
+
+    match first {
+        '*' => (0..=text.len()).any(|skip| match_from(rest, &text[skip..])),
+        '?' => !text.is_empty() && match_from(rest, &text[1..]),
+        '[' => match (text.first(), match_bracket(rest)) {
+            (Some(&ch), Some((set, rest))) => set.contains(ch) && match_from(rest, &text[1..]),
+            (Some(&'['), None) => match_from(rest, &text[1..]),
+            _ => false,
+        },
+        '\\' if !rest.is_empty() => {
+            text.first() == Some(&rest[0]) && match_from(&rest[1..], &text[1..])
+        }
+        ch => text.first() == Some(&ch) && match_from(rest, &text[1..]),
+    }
+}
+
+struct BracketSet {
+    negated: bool,
+    items: Vec<BracketItem>,
+}
+
+enum BracketItem {
+    Char(char),
+    Range(char, char),
+    Class(String),
+}
+
+impl BracketSet {
+    fn contains(&self, ch: char) -> bool {
+        let found = self.items.iter().any(|item| match item {
+            BracketItem::Char(c) => *c == ch,
+            BracketItem::Range(start, end) => (*start..=*end).contains(&ch),
+            BracketItem::Class(class) => match class.as_str() {
+                "alpha" => ch.is_alphabetic(),
+                "digit" => ch.is_ascii_digit(),
+                "alnum" => ch.is_alphanumeric(),
+                "upper" => ch.is_uppercase(),
+                "lower" => ch.is_lowercase(),
+                "space" => ch.is_whitespace(),
+                "punct" => ch.is_ascii_punctuation(),
+                "xdigit" => ch.is_ascii_hexdigit(),
+                _ => false,
+            },
+        });
+
+        found != self.negated
+    }
+}
+
+/// Parses the inside of a bracket expression, `pattern` starts right after the `[`.
+/// Returns `None` when the bracket isn't closed, in which case `[` is a literal.
+fn match_bracket(pattern: &[char]) -> Option<(BracketSet, &[char])> {
+    let mut index = 0;
+    let negated = matches!(pattern.first(), Some('!' | '^'));
+
+    if negated {
+        index += 1;
+    }
+
+    let mut items = Vec::new();
+    let start = index;
+
+    while index < pattern.len() {
+        let ch = pattern[index];
+
+        if ch == ']' && index > start {
+            return Some((BracketSet { negated, items }, &pattern[index + 1..]));
+        }
+
+        if ch == '[' && pattern.get(index + 1) == Some(&':') {
+            let name_start = index + 2;
+            let name_end = pattern[name_start..]
+                .windows(2)
+                .position(|pair| pair == [':', ']']);
+
+            if let Some(length) = name_end {
+                let name = pattern[name_start..name_start + length].iter().collect();
+                items.push(BracketItem::Class(name));
+                index = name_start + length + 2;
+                continue;
+            }
+        }
+
+        let ch = match ch {
+            '\\' if index + 1 < pattern.len() => {
+                index += 1;
+                pattern[index]
+            }
+            ch => ch,
+        };
+
+        match (pattern.get(index + 1), pattern.get(index + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                items.push(BracketItem::Range(ch, end));
+                index += 3;
+            }
+            _ => {
+                items.push(BracketItem::Char(ch));
+                index += 1;
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,34 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Where the grapheme starting at byte `index` of `text` ends, the place the cursor goes
+/// when it moves right.
+pub fn next_boundary(text: &str, index: usize) -> usize {
+    text[index..]
+        .graphemes(true)
+        .next()
+        .map_or(index, |grapheme| index + grapheme.len())
+}
+
+/// Where the grapheme before byte `index` of `text` starts, the place the cursor goes when
+/// it moves left.
+pub fn previous_boundary(text: &str, index: usize) -> usize {
+    text[..index]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start)
+}
+
+/// The graphemes of `text`, what the user sees as characters, with where they start.
+pub fn graphemes(text: &str) -> Vec<(usize, &str)> {
+    text.grapheme_indices(true).collect()
+}
+
+/// Columns `text` takes on the screen. Control characters take none, the terminal doesn't
+/// draw them.
+pub fn width(text: &str) -> usize {
+    text.graphemes(true)
+        .filter(|grapheme| !grapheme.starts_with(char::is_control))
+        .map(UnicodeWidthStr::width)
+        .sum()
+}
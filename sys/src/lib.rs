@@ -0,0 +1,129 @@
+//! The calls into libc that need `unsafe`, in a crate of their own so the shell can forbid
+//! unsafe code everywhere else. Each one is wrapped in a safe function.
+#![warn(rust_2018_idioms, unreachable_pub, clippy::all)]
+
+use std::{
+    io::Error,
+    os::{
+        fd::{AsRawFd, OwnedFd},
+        unix::process::CommandExt,
+    },
+    process::Command,
+};
+
+pub use libc::{c_int, tm};
+
+/// Which side of a `fork` the caller is on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fork {
+    Child,
+    Parent(u32),
+}
+
+/// What a signal did before `ignore_signal` changed it, for `restore_signal`.
+pub struct Disposition(libc::sighandler_t);
+
+/// Forks the process. Only the calling thread goes on in the child, so the caller must not
+/// have any other thread, and the child should end with `exit_now`.
+pub fn fork() -> Result<Fork, Error> {
+    // SAFETY: the child runs arbitrary code that allocates and takes locks, so no other
+    // thread may hold one at the fork, or the child would wait on it forever. The shell never
+    // runs more than its main thread: its runtime is single-threaded and it starts no threads
+    // of its own, which is what callers promise above.
+    match unsafe { libc::fork() } {
+        -1 => Err(Error::last_os_error()),
+        0 => Ok(Fork::Child),
+        pid => Ok(Fork::Parent(pid as u32)),
+    }
+}
+
+/// Ends a forked child right away, without the parent's exit handlers or buffers.
+pub fn exit_now(status: i32) -> ! {
+    // SAFETY: `_exit` takes any status and never returns.
+    unsafe { libc::_exit(status) }
+}
+
+/// Gives a signal its default disposition.
+pub fn default_signal(signal: c_int) {
+    // SAFETY: `SIG_DFL` installs no handler, so no code of ours runs on the signal.
+    unsafe { libc::signal(signal, libc::SIG_DFL) };
+}
+
+/// Ignores a signal, returning what it did before.
+pub fn ignore_signal(signal: c_int) -> Disposition {
+    // SAFETY: `SIG_IGN` installs no handler, so no code of ours runs on the signal.
+    Disposition(unsafe { libc::signal(signal, libc::SIG_IGN) })
+}
+
+/// Gives a signal back what it did before `ignore_signal`.
+pub fn restore_signal(signal: c_int, previous: Disposition) {
+    // SAFETY: `previous` can only come from `signal` itself, so it's a disposition that was
+    // in place for this process before.
+    unsafe { libc::signal(signal, previous.0) };
+}
+
+/// Gives `signals` their default disposition in the process of `command` before it runs
+/// its program.
+pub fn default_signals_on_exec(command: &mut Command, signals: &'static [c_int]) {
+    // SAFETY: between fork and exec the hook only calls `signal`, which is async-signal-safe,
+    // and doesn't allocate.
+    unsafe {
+        command.pre_exec(move || {
+            for &signal in signals {
+                libc::signal(signal, libc::SIG_DFL);
+            }
+
+            Ok(())
+        })
+    };
+}
+
+/// Copies each descriptor of `fds` onto its number, then closes the ones in `closed`, in the
+/// process of `command` before it runs its program.
+pub fn move_fds_on_exec(command: &mut Command, fds: Vec<(c_int, OwnedFd)>, closed: Vec<c_int>) {
+    // SAFETY: between fork and exec the hook only calls `dup2` and `close`, which are
+    // async-signal-safe, and doesn't allocate: the vectors were built before the fork.
+    unsafe {
+        command.pre_exec(move || {
+            for (target, source) in &fds {
+                if libc::dup2(source.as_raw_fd(), *target) == -1 {
+                    return Err(Error::last_os_error());
+                }
+            }
+            for &fd in &closed {
+                libc::close(fd);
+            }
+
+            Ok(())
+        })
+    };
+}
+
+/// The broken-down local time of `seconds` since the epoch.
+pub fn local_time(seconds: i64) -> tm {
+    let seconds = seconds as libc::time_t;
+
+    // SAFETY: `tm` is plain data that any bit pattern is valid for, all zeros included.
+    let mut tm: tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers come from references that live for the whole call, and
+    // `localtime_r` only writes into `tm`.
+    unsafe { libc::localtime_r(&seconds, &mut tm) };
+
+    tm
+}
+
+/// The name of the host, empty when it can't be read.
+pub fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+
+    // SAFETY: the buffer is writable for the length passed along with it.
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+
+    if result != 0 {
+        return String::new();
+    }
+
+    let end = buffer.iter().position(|&byte| byte == 0).unwrap_or(0);
+
+    String::from_utf8_lossy(&buffer[..end]).into_owned()
+}
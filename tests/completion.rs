@@ -0,0 +1,252 @@
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use shell_starter_rust::{
+    interpreter::Interpreter,
+    provider::CommandProvider,
+    shell::{
+        completion::{self, Completion},
+        core::{ShellInterpreter, ShellTokenizer},
+        session::Session,
+        variables::Variables,
+    },
+    tokenizer::{Token, Tokenizer},
+};
+
+#[test]
+fn complete_command_names() {
+    let dir = temp_tree("commands");
+    write_file(&dir.join("mytool"), 0o755);
+    write_file(&dir.join("mytoolkit"), 0o755);
+    write_file(&dir.join("mydata"), 0o644);
+    let mut session = session_with_path(&dir);
+    run_in("myfunc() { true; }; alias myalias=ls", &mut session);
+
+    let completion = complete("my", &mut session);
+    assert_eq!(
+        completion.candidates(),
+        vec!["myalias", "myfunc", "mytool", "mytoolkit"]
+    );
+    assert_eq!(completion.replacement(), None);
+
+    let completion = complete("echo a | myto", &mut session);
+    assert_eq!(completion.start(), 9);
+    assert_eq!(completion.candidates(), vec!["mytool", "mytoolkit"]);
+    assert_eq!(completion.replacement(), Some(String::from("mytool")));
+
+    let completion = complete("if myf", &mut session);
+    assert_eq!(completion.replacement(), Some(String::from("myfunc ")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn complete_files_in_argument_position() {
+    let dir = temp_tree("files");
+    fs::create_dir(dir.join("src")).unwrap();
+    write_file(&dir.join("script.sh"), 0o644);
+    write_file(&dir.join(".hidden"), 0o644);
+    let mut session = session_with_path(&dir);
+    let base = dir.display();
+
+    let completion = complete(&format!("cat {}/s", base), &mut session);
+    assert_eq!(completion.start(), 4);
+    assert_eq!(
+        completion.candidates(),
+        vec![format!("{}/script.sh", base), format!("{}/src/", base)]
+    );
+    assert_eq!(completion.columns(80), vec!["script.sh  src/"]);
+    assert_eq!(completion.columns(10), vec!["script.sh", "src/"]);
+
+    let completion = complete(&format!("cd {}/sr", base), &mut session);
+    assert_eq!(completion.replacement(), Some(format!("{}/src/", base)));
+
+    let completion = complete(&format!("ls {}/", base), &mut session);
+    assert_eq!(completion.candidates().len(), 2);
+    let completion = complete(&format!("ls {}/.h", base), &mut session);
+    assert_eq!(completion.candidates(), vec![format!("{}/.hidden", base)]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn command_paths_only_match_executables() {
+    let dir = temp_tree("command_paths");
+    write_file(&dir.join("run.sh"), 0o755);
+    write_file(&dir.join("readme"), 0o644);
+    let mut session = session_with_path(&dir);
+
+    let completion = complete(&format!("{}/r", dir.display()), &mut session);
+    assert_eq!(
+        completion.candidates(),
+        vec![format!("{}/run.sh", dir.display())]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn quote_spaces_and_expand_home() {
+    let dir = temp_tree("quoting");
+    write_file(&dir.join("my file.txt"), 0o644);
+    let mut session = session_with_path(&dir);
+    session
+        .variables
+        .set("HOME", &dir.display().to_string())
+        .unwrap();
+
+    let completion = complete("cat ~/my", &mut session);
+    assert_eq!(
+        completion.replacement(),
+        Some(String::from("~/my\\ file.txt "))
+    );
+
+    let completion = complete("cat ~/my\\ f", &mut session);
+    assert_eq!(
+        completion.replacement(),
+        Some(String::from("~/my\\ file.txt "))
+    );
+
+    let completion = complete("cat \"~/my", &mut session);
+    assert_eq!(completion.start(), 4);
+    assert_eq!(completion.replacement(), None);
+
+    let completion = complete(&format!("cat \"{}/my", dir.display()), &mut session);
+    assert_eq!(
+        completion.replacement(),
+        Some(format!("\"{}/my file.txt\" ", dir.display()))
+    );
+
+    let completion = complete("cd ~", &mut session);
+    assert_eq!(completion.replacement(), Some(String::from("~/")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn complete_from_word_list() {
+    let mut session = Session::new();
+    run_in("complete -W 'add commit checkout' git", &mut session);
+
+    let completion = complete("git c", &mut session);
+    assert_eq!(completion.candidates(), vec!["checkout", "commit"]);
+    assert_eq!(completion.replacement(), None);
+
+    let completion = complete("cd /tmp && git a", &mut session);
+    assert_eq!(completion.replacement(), Some(String::from("add ")));
+
+    // The spec only applies to arguments.
+    let completion = complete("gi", &mut session);
+    assert!(!completion.candidates().contains(&"add"));
+}
+
+#[test]
+fn complete_from_function() {
+    let mut session = Session::new();
+    let input = "_words() { COMPREPLY=\"$1/$3 $COMP_CWORD
+$2|$COMP_WORDS\"; seen=yes; echo noise; echo error >&2; return 1; }; \
+                 complete -F _words tool";
+    run_in(input, &mut session);
+    session.last_status = 7;
+
+    let completion = complete("tool -x \"a b", &mut session);
+    // Every line of COMPREPLY is a candidate, COMP_WORDS has a word per line.
+    assert_eq!(
+        completion.candidates(),
+        vec!["-x", "a b", "a b|tool", "tool/-x 2"]
+    );
+    assert_eq!(session.variables.get("seen"), None);
+    assert_eq!(session.last_status, 7);
+    assert_eq!(session.variables.get("COMPREPLY"), None);
+    assert_eq!(session.variables.get("COMP_WORDS"), None);
+}
+
+#[test]
+fn list_and_remove_specs() {
+    let path = env::temp_dir().join(format!("shell_completion_list_{}", std::process::id()));
+    let mut session = Session::new();
+
+    run_in("complete -W 'a b' -F f x y; complete -r y", &mut session);
+    run_in(
+        &format!("complete -p > {0}; complete -p x >> {0}", path.display()),
+        &mut session,
+    );
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "complete -W 'a b' -F f x\ncomplete -W 'a b' -F f x\n"
+    );
+
+    assert_eq!(status_in("complete -r y", &mut session), 1);
+    assert_eq!(status_in("complete -W", &mut session), 1);
+    assert_eq!(status_in("complete -W a", &mut session), 1);
+    assert_eq!(status_in("complete -r; complete -p x", &mut session), 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn spec_files_in_directory() {
+    let dir = temp_tree("spec_files");
+    write_file(&dir.join("git"), 0o644);
+    write_file(&dir.join("cargo"), 0o644);
+    fs::create_dir(dir.join("nested")).unwrap();
+    let mut session = Session::new();
+    session.variables = Variables::default();
+    session
+        .variables
+        .set("SHELL_COMPLETION_DIR", &dir.display().to_string())
+        .unwrap();
+
+    assert_eq!(
+        completion::spec_files(&session),
+        vec![dir.join("cargo"), dir.join("git")]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+fn complete(line: &str, session: &mut Session) -> Completion {
+    Completion::new::<Token, Interpreter, Tokenizer, CommandProvider>(line, line.len(), session)
+}
+
+/// A session whose `PATH` only holds `dir`, without touching the process environment.
+fn session_with_path(dir: &Path) -> Session {
+    let mut session = Session::new();
+    session.variables = Variables::default();
+    session
+        .variables
+        .set("PATH", &dir.display().to_string())
+        .unwrap();
+
+    session
+}
+
+fn run_in(input: &str, session: &mut Session) {
+    assert_eq!(status_in(input, session), 0, "{}", input);
+}
+
+fn status_in(input: &str, session: &mut Session) -> i32 {
+    let tokens = Tokenizer::tokenize(input).unwrap();
+
+    Interpreter::run::<CommandProvider>(&tokens, session).unwrap()
+}
+
+fn write_file(path: &Path, mode: u32) {
+    fs::write(path, "").unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+fn temp_tree(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!(
+        "shell_completion_test_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
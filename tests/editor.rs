@@ -0,0 +1,186 @@
+use shell_starter_rust::shell::editor::Editor;
+
+#[test]
+fn insert_and_delete_at_cursor() {
+    let mut line = editor("ecoh");
+
+    line.move_left();
+    line.move_left();
+    line.insert('h');
+    assert_eq!((line.text(), line.cursor()), ("echoh", 3));
+
+    line.delete_forward();
+    line.move_end();
+    line.delete_backward();
+    assert_eq!(line.text(), "ech");
+
+    line.move_home();
+    line.delete_backward();
+    line.move_left();
+    assert_eq!((line.text(), line.cursor()), ("ech", 0));
+}
+
+#[test]
+fn move_by_words() {
+    let mut line = editor("git commit --amend");
+
+    line.move_word_left();
+    assert_eq!(line.before_cursor(), "git commit --");
+    line.move_word_left();
+    assert_eq!(line.before_cursor(), "git ");
+
+    line.move_word_right();
+    assert_eq!(line.before_cursor(), "git commit");
+    line.move_word_right();
+    line.move_word_right();
+    assert_eq!(line.cursor(), line.text().len());
+}
+
+#[test]
+fn kill_and_yank() {
+    let mut line = editor("echo one two");
+
+    line.kill_word_backward();
+    assert_eq!(line.text(), "echo one ");
+    line.move_home();
+    line.kill_word_forward();
+    assert_eq!(line.text(), " one ");
+
+    line.move_end();
+    line.yank();
+    assert_eq!(line.text(), " one echo");
+
+    // Alt-Y goes back through the ring, and around it.
+    line.yank_pop();
+    assert_eq!(line.text(), " one two");
+    line.yank_pop();
+    assert_eq!(line.text(), " one echo");
+
+    // Only right after a yank.
+    line.insert('!');
+    line.yank_pop();
+    assert_eq!(line.text(), " one echo!");
+}
+
+#[test]
+fn consecutive_kills_make_one_entry() {
+    let mut line = editor("a b c");
+
+    line.kill_word_backward();
+    line.kill_word_backward();
+    assert_eq!(line.text(), "a ");
+    line.yank();
+    assert_eq!(line.text(), "a b c");
+
+    line.move_home();
+    line.kill_to_end();
+    line.kill_to_end();
+    line.set("x");
+    line.yank();
+    assert_eq!(line.text(), "xa b c");
+    line.yank_pop();
+    assert_eq!(line.text(), "xb c");
+}
+
+#[test]
+fn transpose_characters() {
+    let mut line = editor("sl");
+
+    line.transpose();
+    assert_eq!((line.text(), line.cursor()), ("ls", 2));
+
+    line.set("abc");
+    line.move_home();
+    line.transpose();
+    assert_eq!(line.text(), "abc");
+    line.move_right();
+    line.transpose();
+    assert_eq!((line.text(), line.cursor()), ("bac", 2));
+}
+
+#[test]
+fn multibyte_characters() {
+    let mut line = editor("héllo");
+
+    line.move_left();
+    line.move_left();
+    line.move_left();
+    line.delete_backward();
+    assert_eq!((line.text(), line.cursor()), ("hllo", 1));
+    line.insert('é');
+    assert_eq!(line.before_cursor(), "hé");
+}
+
+#[test]
+fn moves_over_graphemes() {
+    let mut line = editor("e\u{301}日👍🏽🇫🇷");
+
+    line.move_left();
+    assert_eq!(line.before_cursor(), "e\u{301}日👍🏽");
+    line.delete_backward();
+    assert_eq!(line.text(), "e\u{301}日🇫🇷");
+
+    line.move_home();
+    line.move_right();
+    assert_eq!(line.before_cursor(), "e\u{301}");
+    line.delete_forward();
+    line.insert('本');
+    assert_eq!(
+        (line.text(), line.before_cursor()),
+        ("e\u{301}本🇫🇷", "e\u{301}本")
+    );
+
+    line.transpose();
+    assert_eq!(line.text(), "e\u{301}🇫🇷本");
+}
+
+#[test]
+fn moves_between_lines() {
+    let mut line = editor("if true\nthen echo 日本\nfi");
+
+    assert!(!line.move_down());
+    assert!(line.move_up());
+    assert_eq!(line.before_cursor(), "if true\nth");
+    assert!(line.move_up());
+    assert_eq!(line.before_cursor(), "if");
+    assert!(!line.move_up());
+
+    line.move_to("if true\nthen echo 日".len());
+    assert!(line.move_up());
+    assert_eq!(line.before_cursor(), "if true");
+    assert!(line.move_down());
+    assert_eq!(line.before_cursor(), "if true\nthen ec");
+}
+
+#[test]
+fn edits_the_current_line() {
+    let mut line = editor("if true\nthen echo a\nfi");
+
+    line.move_to("if true\nthen".len());
+    line.move_home();
+    assert_eq!(line.before_cursor(), "if true\n");
+    line.move_end();
+    assert_eq!(line.before_cursor(), "if true\nthen echo a");
+
+    line.move_to("if true\nthen".len());
+    line.kill_to_end();
+    assert_eq!(line.text(), "if true\nthen\nfi");
+    line.kill_to_end();
+    assert_eq!(line.text(), "if true\nthenfi");
+    line.kill_to_start();
+    assert_eq!(
+        (line.text(), line.before_cursor()),
+        ("if true\nfi", "if true\n")
+    );
+
+    // The kills in a row went into one entry.
+    line.yank();
+    assert_eq!(line.text(), "if true\nthen echo a\nfi");
+}
+
+fn editor(text: &str) -> Editor {
+    let mut line = Editor::new();
+    line.set(text);
+
+    line
+}
@@ -0,0 +1,114 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use shell_starter_rust::util::{glob, pattern};
+
+#[test]
+fn match_wildcards() {
+    assert!(pattern::matches("*.rs", "main.rs"));
+    assert!(pattern::matches("ma?n.rs", "main.rs"));
+    assert!(!pattern::matches("*.rs", "main.rs.bak"));
+    assert!(pattern::matches("\\*", "*"));
+    assert!(!pattern::matches("\\*", "a"));
+}
+
+#[test]
+fn match_bracket_expressions() {
+    assert!(pattern::matches("[a-c]x", "bx"));
+    assert!(!pattern::matches("[!a-c]x", "bx"));
+    assert!(pattern::matches("[^a-c]x", "dx"));
+    assert!(pattern::matches("[[:digit:]]", "7"));
+    assert!(pattern::matches("[]]", "]"));
+    assert!(pattern::matches("[x", "[x"));
+}
+
+#[test]
+fn expand_sorted_matches() {
+    let dir = temp_tree("sorted");
+
+    assert_eq!(
+        glob::expand(&format!("{}/*.txt", dir.display()), false),
+        vec![path(&dir, "a.txt"), path(&dir, "b.txt")]
+    );
+    assert_eq!(
+        glob::expand(&format!("{}/[!a]*", dir.display()), false),
+        vec![path(&dir, "b.txt"), path(&dir, "sub")]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn skip_hidden_files() {
+    let dir = temp_tree("hidden");
+
+    assert!(!glob::expand(&format!("{}/*", dir.display()), false).contains(&path(&dir, ".hidden")));
+    assert_eq!(
+        glob::expand(&format!("{}/.h*", dir.display()), false),
+        vec![path(&dir, ".hidden")]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn expand_directories_only() {
+    let dir = temp_tree("directories");
+
+    assert_eq!(
+        glob::expand(&format!("{}/*/", dir.display()), false),
+        vec![format!("{}/", path(&dir, "sub"))]
+    );
+    assert_eq!(
+        glob::expand(&format!("{}/*/*.txt", dir.display()), false),
+        vec![path(&dir, "sub/c.txt")]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn recursive_globstar() {
+    let dir = temp_tree("globstar");
+    let pattern = format!("{}/**/*.txt", dir.display());
+
+    assert_eq!(
+        glob::expand(&pattern, true),
+        vec![
+            path(&dir, "a.txt"),
+            path(&dir, "b.txt"),
+            path(&dir, "sub/c.txt"),
+            path(&dir, "sub/deep/d.txt"),
+        ]
+    );
+    assert_eq!(glob::expand(&pattern, false), vec![path(&dir, "sub/c.txt")]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn no_match() {
+    let dir = temp_tree("no_match");
+
+    assert!(glob::expand(&format!("{}/*.rs", dir.display()), false).is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+fn temp_tree(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("shell_glob_test_{}_{}", name, std::process::id()));
+
+    fs::create_dir_all(dir.join("sub/deep")).unwrap();
+
+    for file in ["a.txt", "b.txt", ".hidden", "sub/c.txt", "sub/deep/d.txt"] {
+        fs::write(dir.join(file), "").unwrap();
+    }
+
+    dir
+}
+
+fn path(dir: &Path, name: &str) -> String {
+    format!("{}/{}", dir.display(), name)
+}
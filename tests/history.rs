@@ -0,0 +1,171 @@
+use std::{env, fs, path::PathBuf};
+
+use shell_starter_rust::shell::{history::History, search::ReverseSearch, variables::Variables};
+
+#[test]
+fn navigate_entries() {
+    let mut history = History::new(None, 10, 10);
+
+    history.push("first");
+    history.push("second");
+
+    assert_eq!(history.older(""), Some("second"));
+    assert_eq!(history.older(""), Some("first"));
+    assert_eq!(history.older(""), None);
+    assert_eq!(history.newer(), Some("second"));
+}
+
+#[test]
+fn restore_in_progress_line() {
+    let mut history = History::new(None, 10, 10);
+
+    history.push("ls");
+
+    assert_eq!(history.older("echo unfinished"), Some("ls"));
+    assert_eq!(history.newer(), Some("echo unfinished"));
+    assert_eq!(history.newer(), None);
+}
+
+#[test]
+fn ignore_blank_entries() {
+    let mut history = History::new(None, 10, 10);
+
+    history.push("   ");
+
+    assert!(history.entries().is_empty());
+}
+
+#[test]
+fn limit_history_size() {
+    let mut history = History::new(None, 2, 2);
+
+    history.push("one");
+    history.push("two");
+    history.push("three");
+
+    assert_eq!(history.entries(), ["two", "three"]);
+}
+
+#[test]
+fn save_and_load() {
+    let path = temp_file("save_and_load");
+
+    let mut history = History::new(Some(path.clone()), 10, 2);
+    history.push("one");
+    history.push("two");
+    history.push("three");
+    history.save().unwrap();
+
+    let mut loaded = History::new(Some(path.clone()), 10, 10);
+    loaded.load().unwrap();
+
+    assert_eq!(loaded.entries(), ["two", "three"]);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn save_and_load_multiline_entries() {
+    let path = temp_file("multiline");
+
+    let mut history = History::new(Some(path.clone()), 10, 10);
+    history.push("if true\nthen\n\n  echo 'a\\\nb'\nfi");
+    history.push("echo \\\\");
+    history.push("ls");
+    history.save().unwrap();
+
+    let mut loaded = History::new(Some(path.clone()), 10, 10);
+    loaded.load().unwrap();
+
+    assert_eq!(
+        loaded.entries(),
+        ["if true\nthen\n\n  echo 'a\\\nb'\nfi", "echo \\\\ ", "ls"]
+    );
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn settings_from_shell_variables() {
+    let path = temp_file("settings");
+
+    let mut history = History::new(Some(path.clone()), 10, 10);
+    history.push("one");
+    history.push("two");
+    history.push("three");
+    history.save().unwrap();
+
+    let mut variables = Variables::default();
+    variables.set("HISTFILE", &path.to_string_lossy()).unwrap();
+    variables.set("HISTSIZE", "2").unwrap();
+
+    let mut loaded = History::from_variables(&variables);
+    loaded.load().unwrap();
+    assert_eq!(loaded.entries(), ["two", "three"]);
+
+    // An empty `HISTFILE` keeps no file.
+    variables.set("HISTFILE", "").unwrap();
+    let mut loaded = History::from_variables(&variables);
+    loaded.load().unwrap();
+    assert!(loaded.entries().is_empty());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn load_missing_file() {
+    let mut history = History::new(Some(temp_file("missing")), 10, 10);
+
+    assert!(history.load().is_ok());
+    assert!(history.entries().is_empty());
+}
+
+#[test]
+fn search_most_recent_match() {
+    let mut history = History::new(None, 10, 10);
+
+    history.push("cargo build");
+    history.push("ls");
+    history.push("cargo test");
+
+    assert_eq!(history.search("cargo", 3), Some(2));
+    assert_eq!(history.search("cargo", 2), Some(0));
+    assert_eq!(history.search("make", 3), None);
+}
+
+#[test]
+fn reverse_search_narrows_and_cycles() {
+    let mut history = History::new(None, 10, 10);
+
+    history.push("cargo build");
+    history.push("cat notes.md");
+    history.push("cargo test");
+
+    let mut search = ReverseSearch::new("");
+
+    search.push('c', &history);
+    search.push('a', &history);
+    assert_eq!(search.matched(&history), Some("cargo test"));
+
+    search.push('t', &history);
+    assert_eq!(search.matched(&history), Some("cat notes.md"));
+
+    search.pop(&history);
+    search.older(&history);
+    assert_eq!(search.matched(&history), Some("cat notes.md"));
+
+    search.older(&history);
+    assert_eq!(search.matched(&history), Some("cargo build"));
+
+    search.older(&history);
+    assert!(search.is_failed());
+    assert_eq!(search.matched(&history), Some("cargo build"));
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "shell_history_test_{}_{}",
+        name,
+        std::process::id()
+    ))
+}
@@ -0,0 +1,1175 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+
+use shell_starter_rust::{
+    interpreter::Interpreter,
+    provider::CommandProvider,
+    shell::{
+        core::{ShellInterpreter, ShellTokenizer},
+        jobs::JobState,
+        session::Session,
+    },
+    tokenizer::Tokenizer,
+};
+
+// Pipelines
+
+#[test]
+fn pipe_between_external_commands() {
+    assert_status("printf 'b\\na\\n' | sort | head -n 1 | grep -qx a", 0);
+}
+
+#[test]
+fn pipe_builtin_into_external() {
+    assert_status(
+        "type type | tr a-z A-Z | grep -q 'TYPE IS A SHELL BUILTIN'",
+        0,
+    );
+}
+
+#[test]
+fn pipe_external_into_builtin() {
+    assert_status("ls | type cd > /dev/null", 0);
+}
+
+#[test]
+fn status_comes_from_last_command() {
+    assert_status("false | true", 0);
+    assert_status("true | false", 1);
+}
+
+#[test]
+fn pipefail_reports_failing_stage() {
+    assert_status("set -o pipefail; false | true", 1);
+}
+
+#[test]
+fn empty_pipeline_stage() {
+    assert!(run("ls | | wc", &mut Session::new()).is_err());
+}
+
+// Lists
+
+#[test]
+fn sequential_commands() {
+    assert_status("false; true", 0);
+    assert_status("true; false", 1);
+}
+
+#[test]
+fn and_short_circuits() {
+    assert_status("false && true", 1);
+    assert_status("true && false", 1);
+    assert_status("true && true", 0);
+}
+
+#[test]
+fn or_short_circuits() {
+    assert_status("false || true", 0);
+    assert_status("false || false", 1);
+    assert_status("true || false", 0);
+}
+
+#[test]
+fn mixed_list_operators() {
+    assert_status("false && true || true", 0);
+    assert_status("true || false && false", 1);
+}
+
+#[test]
+fn last_status_parameter() {
+    assert_status("false; test $? -eq 1", 0);
+    assert_status("true; test \"$?\" = 0", 0);
+}
+
+#[test]
+fn dangling_list_operator() {
+    assert!(run("true &&", &mut Session::new()).is_err());
+    assert!(run("; true", &mut Session::new()).is_err());
+}
+
+// Parameter expansion
+
+#[test]
+fn expand_named_variable() {
+    let mut session = session_with(&[("NAME", "world")]);
+
+    assert_eq!(run("test $NAME = world", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${NAME}s = worlds", &mut session).unwrap(), 0);
+    assert_eq!(run("test \"$NAME\" = world", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn expand_defaults_and_alternatives() {
+    let mut session = session_with(&[("EMPTY", "")]);
+
+    assert_eq!(run("test ${UNSET:-d} = d", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${EMPTY:-d} = d", &mut session).unwrap(), 0);
+    assert_eq!(run("test \"${EMPTY-d}\" = ''", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${EMPTY+set} = set", &mut session).unwrap(), 0);
+    assert_eq!(run("test \"${UNSET+set}\" = ''", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn expand_assigns_default() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run("test ${ASSIGNED:=value} = value", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(session.variables.get("ASSIGNED"), Some("value"));
+}
+
+#[test]
+fn expand_error_when_unset() {
+    let mut session = Session::new();
+
+    assert!(run("echo ${UNSET:?missing}", &mut session).is_err());
+    assert_eq!(session.last_status, 1);
+
+    // The rest of the input doesn't run.
+    assert!(run("s=before; true; echo ${UNSET?}; s=after", &mut session).is_err());
+    assert_eq!(session.last_status, 1);
+    assert_eq!(session.variables.get("s"), Some("before"));
+}
+
+#[test]
+fn expand_substitutions_in_word() {
+    let mut session = session_with(&[("SET", "x")]);
+
+    run(
+        "a=${UNSET:-$(echo sub)}; b=${SET:+`echo tick`}",
+        &mut session,
+    )
+    .unwrap();
+    assert_eq!(session.variables.get("a"), Some("sub"));
+    assert_eq!(session.variables.get("b"), Some("tick"));
+
+    run(
+        "c=\"${UNSET:-'a  b' \"$SET\"}\"; : ${D:=$(echo $SET)}",
+        &mut session,
+    )
+    .unwrap();
+    assert_eq!(session.variables.get("c"), Some("a  b x"));
+    assert_eq!(session.variables.get("D"), Some("x"));
+}
+
+#[test]
+fn expand_length_and_trimming() {
+    let mut session = session_with(&[("FILE", "archive.tar.gz")]);
+
+    assert_eq!(run("test ${#FILE} -eq 14", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${FILE#*.} = tar.gz", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${FILE##*.} = gz", &mut session).unwrap(), 0);
+    assert_eq!(
+        run("test ${FILE%.*} = archive.tar", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(run("test ${FILE%%.*} = archive", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn expand_replacement() {
+    let mut session = session_with(&[("PATHS", "a/b/c")]);
+
+    assert_eq!(run("test ${PATHS/\\//-} = a-b/c", &mut session).unwrap(), 0);
+    assert_eq!(
+        run("test ${PATHS//\\//-} = a-b-c", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(run("test ${PATHS/#a/x} = x/b/c", &mut session).unwrap(), 0);
+    assert_eq!(run("test ${PATHS/%c/x} = a/b/x", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn bad_substitution() {
+    assert_status("echo ${!!}", 1);
+}
+
+#[test]
+fn unquoted_expansion_splits_fields() {
+    let mut session = session_with(&[("WORDS", "  one two\tthree ")]);
+
+    assert_eq!(run("test $WORDS = one", &mut session).unwrap(), 2);
+    assert_eq!(run("test \"$WORDS\" = one", &mut session).unwrap(), 1);
+    assert_eq!(
+        run("printf '%s\\n' $WORDS | wc -l | grep -qx 3", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(run("test -z $UNSET", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn custom_field_separator() {
+    let mut session = session_with(&[("IFS", ":"), ("LIST", "a::b:")]);
+
+    assert_eq!(
+        run("printf '%s\\n' $LIST | wc -l | grep -qx 3", &mut session).unwrap(),
+        0
+    );
+
+    session.variables.set("IFS", "").unwrap();
+    assert_eq!(
+        run("printf '%s\\n' $LIST | wc -l | grep -qx 1", &mut session).unwrap(),
+        0
+    );
+}
+
+// Variables
+
+#[test]
+fn assignment_sets_shell_variable() {
+    let mut session = Session::new();
+
+    assert_eq!(run("SHELL_TEST_ASSIGN=1", &mut session).unwrap(), 0);
+    assert_eq!(session.variables.get("SHELL_TEST_ASSIGN"), Some("1"));
+    assert_eq!(
+        run("sh -c 'test -z \"$SHELL_TEST_ASSIGN\"'", &mut session).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn assignment_value_is_not_split() {
+    let mut session = session_with(&[("SHELL_TEST_SPACED", "a  b")]);
+
+    assert_eq!(
+        run(
+            "SHELL_TEST_COPY=$SHELL_TEST_SPACED; test \"$SHELL_TEST_COPY\" = 'a  b'",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn export_passes_variable_to_children() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run(
+            "export SHELL_TEST_EXPORT=2; sh -c 'test \"$SHELL_TEST_EXPORT\" = 2'",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(
+        run("env | grep -qx SHELL_TEST_EXPORT=2", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(
+        run(
+            "export -n SHELL_TEST_EXPORT; env | grep -q SHELL_TEST_EXPORT",
+            &mut session
+        )
+        .unwrap(),
+        1
+    );
+    assert_eq!(session.variables.get("SHELL_TEST_EXPORT"), Some("2"));
+}
+
+#[test]
+fn prefix_assignment_only_applies_to_child() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run(
+            "SHELL_TEST_PREFIX=3 sh -c 'test \"$SHELL_TEST_PREFIX\" = 3'",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(session.variables.get("SHELL_TEST_PREFIX"), None);
+}
+
+#[test]
+fn prefix_assignment_applies_to_builtin() {
+    let mut session = session_with(&[("SHELL_TEST_BUILTIN", "old")]);
+
+    run("IFS=: read a b <<< \"1:2\"", &mut session).unwrap();
+    assert_eq!(session.variables.get("a"), Some("1"));
+    assert_eq!(session.variables.get("b"), Some("2"));
+    assert_eq!(session.variables.get("IFS"), None);
+
+    assert_eq!(
+        run(
+            "SHELL_TEST_BUILTIN=new env | grep -qx SHELL_TEST_BUILTIN=new",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(session.variables.get("SHELL_TEST_BUILTIN"), Some("old"));
+    assert!(!session.variables.is_exported("SHELL_TEST_BUILTIN"));
+
+    // A special builtin keeps them.
+    run(
+        "SHELL_TEST_SPECIAL=1 export SHELL_TEST_OTHER=2",
+        &mut session,
+    )
+    .unwrap();
+    assert_eq!(session.variables.get("SHELL_TEST_SPECIAL"), Some("1"));
+}
+
+#[test]
+fn unset_removes_variable() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run("SHELL_TEST_UNSET=1; unset SHELL_TEST_UNSET", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(session.variables.get("SHELL_TEST_UNSET"), None);
+    assert_eq!(run("unset 1INVALID", &mut session).unwrap(), 1);
+}
+
+#[test]
+fn readonly_variable_cannot_change() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run("readonly SHELL_TEST_READONLY=4", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(run("SHELL_TEST_READONLY=5", &mut session).unwrap(), 1);
+    assert_eq!(run("unset SHELL_TEST_READONLY", &mut session).unwrap(), 1);
+    assert_eq!(session.variables.get("SHELL_TEST_READONLY"), Some("4"));
+}
+
+#[test]
+fn set_lists_variables() {
+    let mut session = session_with(&[("SHELL_TEST_LISTED", "a b")]);
+
+    assert_eq!(
+        run("set | grep -qx \"SHELL_TEST_LISTED='a b'\"", &mut session).unwrap(),
+        0
+    );
+}
+
+// Globbing
+
+#[test]
+fn glob_expands_unquoted_words() {
+    let dir = temp_dir("glob_expands");
+
+    assert_status(
+        &format!(
+            "printf '%s\\n' {}/*.txt | wc -l | grep -qx 2",
+            dir.display()
+        ),
+        0,
+    );
+    assert_status(
+        &format!("test {}/a* = {}/a.txt", dir.display(), dir.display()),
+        0,
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn glob_keeps_quoted_and_escaped_words() {
+    let dir = temp_dir("glob_quoted");
+
+    assert_status(&format!("test -e \"{}/*.txt\"", dir.display()), 1);
+    assert_status(&format!("test -e {}/\\*.txt", dir.display()), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn glob_without_match() {
+    let dir = temp_dir("glob_without_match");
+    let pattern = format!("{}/*.rs", dir.display());
+    let mut session = Session::new();
+
+    assert_eq!(
+        run(&format!("test {} = '{}'", pattern, pattern), &mut session).unwrap(),
+        0
+    );
+    assert_eq!(
+        run(
+            &format!("shopt -s nullglob; test -z {}", pattern),
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(
+        run(
+            &format!("shopt -s failglob; echo {}", pattern),
+            &mut session
+        )
+        .unwrap(),
+        1
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn shopt_toggles_confirmpaste() {
+    let mut session = Session::new();
+
+    run("shopt -s confirmpaste", &mut session).unwrap();
+    assert!(session.options.confirmpaste);
+
+    run("shopt -u confirmpaste", &mut session).unwrap();
+    assert!(!session.options.confirmpaste);
+    assert_status("set -o confirmpaste", 1);
+}
+
+#[test]
+fn shopt_rejects_unknown_option() {
+    assert_status("shopt -s nosuchoption", 1);
+    assert_status("set -o nullglob", 1);
+}
+
+// Command substitution
+
+#[test]
+fn substitute_command_output() {
+    assert_status("test $(echo hello) = hello", 0);
+    assert_status("test `echo hello` = hello", 0);
+    assert_status("test \"$(printf 'a\\n\\n\\n')\" = a", 0);
+    assert_status("test $(echo $(echo nested)) = nested", 0);
+}
+
+#[test]
+fn substitute_pipelines_and_builtins() {
+    assert_status("test \"$(echo hi | tr a-z A-Z)\" = HI", 0);
+    assert_status("test \"$(type cd)\" = 'cd is a shell builtin'", 0);
+}
+
+#[test]
+fn substitution_runs_in_subshell() {
+    let mut session = Session::new();
+    let directory = env::current_dir().unwrap();
+
+    let input = "x=1; f() { :; }; y=$(x=2; unset -f f; alias a=b; set -o pipefail; cd /; echo $x)";
+    run(input, &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("1"));
+    assert_eq!(session.variables.get("y"), Some("2"));
+    assert!(session.functions.contains_key("f"));
+    assert!(session.aliases.is_empty());
+    assert!(!session.options.pipefail);
+    assert_eq!(env::current_dir().unwrap(), directory);
+}
+
+#[test]
+fn unquoted_substitution_splits_fields() {
+    assert_status("test $(echo a b) = a", 2);
+    assert_status("test \"$(echo a b)\" = 'a b'", 0);
+}
+
+#[test]
+fn case_in_substitution() {
+    assert_status("test $(case x in x) echo y;; esac) = y", 0);
+    assert_status("test \"$(case a in (b) echo b;; *) echo c;; esac)\" = c", 0);
+}
+
+#[test]
+fn substitution_in_assignment() {
+    let mut session = Session::new();
+
+    assert_eq!(
+        run("SHELL_TEST_SUBST=$(echo a b)", &mut session).unwrap(),
+        0
+    );
+    assert_eq!(session.variables.get("SHELL_TEST_SUBST"), Some("a b"));
+}
+
+// Redirections
+
+#[test]
+fn redirect_external_output() {
+    let path = temp_file("redirect_external_output");
+
+    assert_status(&format!("printf 'a\\nb\\n' > {}", path.display()), 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\n");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn redirect_builtin_output() {
+    let path = temp_file("redirect_builtin_output");
+
+    assert_status(&format!("echo hello > {}", path.display()), 0);
+    assert_status(&format!("type type >> {}", path.display()), 0);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "hello\ntype is a shell builtin\n"
+    );
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn redirect_last_pipeline_stage() {
+    let path = temp_file("redirect_last_pipeline_stage");
+
+    assert_status(&format!("printf 'b\\na\\n' | sort > {}", path.display()), 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\n");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn redirect_both_streams() {
+    let out = temp_file("redirect_both_streams_out");
+    let err = temp_file("redirect_both_streams_err");
+
+    assert_status(
+        &format!("ls / /nonexistent > {} 2> {}", out.display(), err.display()),
+        2,
+    );
+    assert!(!fs::read_to_string(&out).unwrap().is_empty());
+    assert!(fs::read_to_string(&err).unwrap().contains("nonexistent"));
+
+    fs::remove_file(out).unwrap();
+    fs::remove_file(err).unwrap();
+}
+
+#[test]
+fn redirect_input_from_file() {
+    let path = temp_file("redirect_input_from_file");
+
+    fs::write(&path, "b\na\n").unwrap();
+    assert_status(
+        &format!("sort < {} | head -n 1 | grep -qx a", path.display()),
+        0,
+    );
+    assert_status(&format!("grep -qx b <{}", path.display()), 0);
+    assert_status("cat < /nonexistent", 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn here_document_expands_body() {
+    let mut session = session_with(&[("SHELL_TEST_HEREDOC", "value")]);
+
+    assert_eq!(
+        run(
+            "grep -qx 'value sub' <<EOF\n$SHELL_TEST_HEREDOC $(echo sub)\nEOF",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+    assert_eq!(
+        run("grep -qx 'tabbed' <<-EOF\n\ttabbed\n\tEOF", &mut session).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn quoted_here_document_stays_literal() {
+    let mut session = session_with(&[("SHELL_TEST_HEREDOC", "value")]);
+
+    assert_eq!(
+        run(
+            "grep -qx '$SHELL_TEST_HEREDOC' <<'EOF'\n$SHELL_TEST_HEREDOC\nEOF",
+            &mut session
+        )
+        .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn here_string_feeds_stdin() {
+    assert_status("tr a-z A-Z <<< 'a b' | grep -qx 'A B'", 0);
+}
+
+#[test]
+fn duplicate_stderr_into_stdout() {
+    let path = temp_file("duplicate_stderr_into_stdout");
+
+    assert_status(&format!("ls / /nonexistent > {} 2>&1", path.display()), 2);
+    assert!(fs::read_to_string(&path).unwrap().contains("nonexistent"));
+    assert_status("ls /nonexistent 2>&1 | grep -q nonexistent", 0);
+    assert_status("sh -c 'echo err >&2' 2>&1 | grep -qx err", 0);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn redirections_apply_left_to_right() {
+    assert_status("ls /nonexistent 2>&1 > /dev/null | grep -q nonexistent", 0);
+    assert_status("ls /nonexistent > /dev/null 2>&1 | grep -q nonexistent", 1);
+}
+
+#[test]
+fn redirect_both_streams_at_once() {
+    let path = temp_file("redirect_both_streams_at_once");
+
+    assert_status(&format!("ls / /nonexistent &> {}", path.display()), 2);
+    assert_status(&format!("echo appended &>> {}", path.display()), 0);
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("nonexistent"));
+    assert!(content.ends_with("appended\n"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn duplicate_other_descriptors() {
+    let path = temp_file("duplicate_other_descriptors");
+
+    assert_status(&format!("echo hi 3> {} >&3", path.display()), 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hi\n");
+    assert_status(&format!("grep -qx hi 4<> {} <&4", path.display()), 0);
+    assert_status("echo hi >&5", 1);
+    assert_status(&format!("echo hi 10> {} >&10", path.display()), 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hi\n");
+    assert_status("echo hi 99999999>/dev/null", 1);
+    assert_status("echo hi >&-", 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn external_commands_get_other_descriptors() {
+    let path = temp_file("external_commands_get_other_descriptors");
+
+    let input = format!("sh -c 'echo three >&3' 3> {}", path.display());
+    assert_status(&input, 0);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "three\n");
+
+    assert_status("sh -c 'echo dup >&3' 3>&1 | grep -qx dup", 0);
+    assert_status("sh -c 'echo nine >&9' 9>&1 4</dev/null | grep -qx nine", 0);
+    assert_status("cat /dev/fd/12 <<< ten 12<&0 | grep -qx ten", 0);
+
+    // A closed descriptor is closed in the command, not `/dev/null`.
+    assert_status("sh -c 'test -e /dev/fd/1' >&-", 1);
+    assert_status("sh -c 'test -e /dev/fd/0' <&-", 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn builtin_write_to_closed_stdout_fails() {
+    let path = temp_file("builtin_write_to_closed_stdout_fails");
+
+    assert_status(&format!("echo hi >&- 2> {}", path.display()), 1);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "echo: write error: Bad file descriptor\n"
+    );
+
+    assert_status("f() { echo hi; }; f >&-", 1);
+    assert_status("true >&-", 0);
+    assert_status("read line <&-", 1);
+    assert_status("{ cat < /nonexistent; } 2>&-", 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn noclobber_protects_existing_files() {
+    let path = temp_file("noclobber_protects_existing_files");
+    let mut session = Session::new();
+
+    fs::write(&path, "old\n").unwrap();
+
+    assert_eq!(
+        run(
+            &format!("set -o noclobber; echo new > {}", path.display()),
+            &mut session
+        )
+        .unwrap(),
+        1
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+    assert_eq!(
+        run(&format!("echo new >| {}", path.display()), &mut session).unwrap(),
+        0
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+
+    fs::remove_file(path).unwrap();
+}
+
+// Positional parameters
+
+#[test]
+fn quoted_at_keeps_parameters_apart() {
+    let mut session = Session::new();
+    session.name = "script".to_string();
+    session.positional = vec!["a b".to_string(), "c".to_string()];
+
+    let status = run(
+        "test $0 = script && test $# = 2 && test \"$1\" = 'a b' && printf '%s\\n' \"$@\" | grep -qx 'a b'",
+        &mut session,
+    );
+
+    assert_eq!(status.unwrap(), 0);
+}
+
+#[test]
+fn braced_positional_parameter() {
+    let mut session = Session::new();
+    session.positional = (1..=10).map(|n| n.to_string()).collect();
+
+    assert_eq!(
+        run("test ${10} = 10 && test $10 = 10", &mut session).unwrap(),
+        0
+    );
+}
+
+// Jobs
+
+#[test]
+fn wait_for_background_job() {
+    assert_status("sh -c 'exit 3' & wait $!", 3);
+    assert_status("sh -c 'exit 4' & wait %1", 4);
+    assert_status("sh -c 'exit 5' & wait", 0);
+}
+
+#[test]
+fn background_job_goes_into_table() {
+    let mut session = Session::new();
+    let start = Instant::now();
+
+    assert_eq!(run("sleep 5 &", &mut session).unwrap(), 0);
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let line = session.jobs.format(1).unwrap();
+    assert_eq!(line, "[1]+  Running                 sleep 5 &");
+
+    assert_eq!(run("kill $!; wait %1", &mut session).unwrap(), 143);
+    assert!(session.jobs.ids().is_empty());
+}
+
+#[test]
+fn bg_continues_stopped_job() {
+    let mut session = Session::new();
+
+    run("sh -c 'kill -STOP $$; exit 6' &", &mut session).unwrap();
+
+    let start = Instant::now();
+    while session.jobs.get(1).unwrap().state != JobState::Stopped {
+        assert!(start.elapsed() < Duration::from_secs(5), "job didn't stop");
+        session.jobs.update().unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(run("bg %1; wait %1", &mut session).unwrap(), 6);
+}
+
+#[test]
+fn stopped_job_ends_the_list() {
+    let mut session = Session::new();
+
+    assert!(run("sh -c 'kill -STOP $$'; s=after", &mut session).is_err());
+    assert_eq!(session.last_status, 148);
+    assert_eq!(session.variables.get("s"), None);
+    assert_eq!(session.jobs.get(1).unwrap().state, JobState::Stopped);
+
+    assert_eq!(run("bg %1; wait %1", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn fg_waits_for_job() {
+    assert_status("sh -c 'sleep 0.1; exit 7' & fg %1", 7);
+    assert_status("fg %1", 1);
+}
+
+#[test]
+fn disown_removes_job() {
+    let mut session = Session::new();
+
+    run("sleep 5 & disown", &mut session).unwrap();
+    assert!(session.jobs.ids().is_empty());
+
+    assert_eq!(run("wait %1", &mut session).unwrap(), 127);
+    run("kill $!", &mut session).unwrap();
+}
+
+#[test]
+fn background_and_or_list() {
+    let mut session = Session::new();
+    let start = Instant::now();
+
+    assert_eq!(run("true && sleep 5 &", &mut session).unwrap(), 0);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(
+        session.jobs.format(1).unwrap(),
+        "[1]+  Running                 true && sleep 5 &"
+    );
+    assert_eq!(run("kill $!; wait %1", &mut session).unwrap(), 143);
+
+    assert_status("false && true || sh -c 'exit 4' & wait $!", 4);
+}
+
+#[test]
+fn background_function_and_compound() {
+    let mut session = Session::new();
+    let start = Instant::now();
+
+    assert_eq!(run("f() { sleep 2; }; f &", &mut session).unwrap(), 0);
+    assert_eq!(run("{ sleep 2; } &", &mut session).unwrap(), 0);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // What runs in the background changes nothing in the shell.
+    run("x=1; { x=2; exit 3; } & wait $!", &mut session).unwrap();
+    assert_eq!(session.last_status, 3);
+    assert_eq!(session.variables.get("x"), Some("1"));
+    assert_status("false; cd / &", 0);
+}
+
+// Compound commands
+
+#[test]
+fn if_elif_else() {
+    assert_status("if true; then true; else false; fi", 0);
+    assert_status("if false; then true; else false; fi", 1);
+    assert_status("if false; then false; elif true; then true; fi", 0);
+    assert_status("if false; then false; fi", 0);
+}
+
+#[test]
+fn while_and_until_loops() {
+    let mut session = Session::new();
+
+    run("x=; while [ \"$x\" != aaa ]; do x=a$x; done", &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("aaa"));
+
+    run("until [ \"$x\" = a ]; do x=${x#a}; done", &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("a"));
+}
+
+#[test]
+fn for_over_words_and_globs() {
+    let dir = temp_dir("for_glob");
+    let mut session = Session::new();
+
+    let input = format!(
+        "s=; for f in x {}/*.txt; do s=$s${{f##*/}},; done",
+        dir.display()
+    );
+    run(&input, &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("x,a.txt,b.txt,"));
+
+    session.positional = vec!["1".to_string(), "2".to_string()];
+    run("s=; for n; do s=$s$n; done", &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("12"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn case_matches_patterns() {
+    let mut session = Session::new();
+
+    let input = "case main.rs in *.c) x=c;; *.toml | *.rs) x=rust;; *) x=other;; esac";
+    run(input, &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("rust"));
+
+    run("case '*' in a) x=a;; \"*\") x=star;; esac", &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("star"));
+}
+
+#[test]
+fn break_and_continue() {
+    let mut session = Session::new();
+
+    let input =
+        "s=; for i in 1 2 3 4; do [ $i = 2 ] && continue; [ $i = 4 ] && break; s=$s$i; done";
+    run(input, &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("13"));
+
+    let input =
+        "s=; for i in a b; do for j in 1 2; do [ $j = 2 ] && continue 2; s=$s$i$j; done; done";
+    run(input, &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("a1b1"));
+
+    assert_status("break", 1);
+}
+
+#[test]
+fn read_lines_from_redirected_loop() {
+    let file = temp_file("read_loop");
+    fs::write(&file, "one two three\nfour\n").unwrap();
+    let mut session = Session::new();
+
+    let input = format!(
+        "s=; while read first rest; do s=\"$s[$first|$rest]\"; done < {}",
+        file.display()
+    );
+    run(&input, &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("[one|two three][four|]"));
+
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn compound_command_in_pipeline() {
+    let file = temp_file("loop_pipeline");
+
+    let input = format!(
+        "for i in b c a; do echo $i; done | sort > {}",
+        file.display()
+    );
+    assert_status(&input, 0);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "a\nb\nc\n");
+
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn compound_stage_streams_into_pipe() {
+    let start = Instant::now();
+
+    assert_status("while true; do echo y; done | head -n 1 | grep -qx y", 0);
+    assert_status(
+        "f() { while true; do echo $1; done; }; f n | head -n 2 | wc -l | grep -qx 2",
+        0,
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    let mut session = Session::new();
+    run("x=1; { x=2; echo; } | cat > /dev/null", &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("1"));
+}
+
+#[test]
+fn interrupt_ends_loops() {
+    let mut session = Session::new();
+
+    let input = "while true; do sh -c 'kill -INT $$'; done; s=after";
+    assert!(run(input, &mut session).is_err());
+    assert_eq!(session.last_status, 130);
+    assert_eq!(session.variables.get("s"), None);
+
+    session.last_status = 0;
+    session.interrupted.store(true, Ordering::Relaxed);
+    assert!(run("for i in a b; do s=$i; done", &mut session).is_err());
+    assert_eq!(session.last_status, 130);
+    assert_eq!(session.variables.get("s"), None);
+}
+
+#[test]
+fn pipes_close_after_forked_stages() {
+    // Every copy of a write end has to close for the reading stage to see the end.
+    assert_status("echo hi | { cat; } | grep -qx hi", 0);
+    assert_status("f() { cat; }; f <<< hi | grep -qx hi", 0);
+    assert_status(
+        "while read l; do echo \"<$l>\"; done <<EOF | grep -qx '<b>'\na\nb\nEOF",
+        0,
+    );
+    assert_status("{ cat; } <<EOF | wc -c | grep -qx 4\nabc\nEOF", 0);
+    assert_status(
+        "echo hi | while read l; do echo \"[$l]\"; done | grep -qx '\\[hi\\]'",
+        0,
+    );
+}
+
+#[test]
+fn incomplete_compound_command() {
+    for input in [
+        "if true; then",
+        "while true; do true",
+        "for i in a",
+        "case a in",
+    ] {
+        let tokens = Tokenizer::tokenize(input).unwrap();
+        let err = Interpreter::check(&tokens).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{}", input);
+    }
+
+    assert!(run("if true; fi", &mut Session::new()).is_err());
+    assert!(run("done", &mut Session::new()).is_err());
+}
+
+// Functions
+
+#[test]
+fn function_gets_own_positional_parameters() {
+    let mut session = Session::new();
+    session.positional = vec!["outer".to_string()];
+
+    run("f() { s=\"$#:$1:$2\"; }; f a 'b c'", &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("2:a:b c"));
+    assert_eq!(session.positional, vec!["outer"]);
+
+    run("function g { s=$1; }; g x", &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("x"));
+}
+
+#[test]
+fn local_variables_are_dynamically_scoped() {
+    let mut session = session_with(&[("v", "global")]);
+
+    let input =
+        "inner() { seen=$v; v=changed; }; outer() { local v=local; inner; after=$v; }; outer";
+    run(input, &mut session).unwrap();
+    assert_eq!(session.variables.get("seen"), Some("local"));
+    assert_eq!(session.variables.get("after"), Some("changed"));
+    assert_eq!(session.variables.get("v"), Some("global"));
+
+    assert_status("local x", 1);
+}
+
+#[test]
+fn return_sets_function_status() {
+    assert_status("f() { return 3; false; }; f", 3);
+    assert_status("f() { for i in 1 2; do return $i; done; }; f", 1);
+    assert_status("f() { false; return; }; f", 1);
+    assert_status("return 2", 1);
+}
+
+#[test]
+fn function_takes_precedence_over_builtin() {
+    let mut session = Session::new();
+
+    run("pwd() { s=function; }; pwd", &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some("function"));
+
+    run("unset -f pwd; s=; pwd > /dev/null", &mut session).unwrap();
+    assert_eq!(session.variables.get("s"), Some(""));
+}
+
+#[test]
+fn function_in_pipeline() {
+    assert_status("f() { echo $1; }; f piped | grep -qx piped", 0);
+}
+
+#[test]
+fn type_reports_functions() {
+    let path = temp_file("type_function");
+    let mut session = Session::new();
+
+    let input = format!(
+        "greet() {{ echo \"hi $1\"; }}; type greet echo > {}",
+        path.display()
+    );
+    assert_eq!(run(&input, &mut session).unwrap(), 0);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "greet is a function\ngreet ()\n{\n    echo \"hi ${1}\"\n}\necho is a shell builtin\n"
+    );
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn runaway_recursion_fails() {
+    assert_status("f() { f; }; f", 1);
+}
+
+// Aliases
+
+#[test]
+fn alias_replaces_first_word() {
+    let mut session = Session::new();
+
+    run("alias setx='x=aliased' fail=false", &mut session).unwrap();
+    run("setx; echo setx > /dev/null", &mut session).unwrap();
+    assert_eq!(session.variables.get("x"), Some("aliased"));
+
+    assert_eq!(run("true && fail", &mut session).unwrap(), 1);
+    assert_eq!(
+        run("if fail; then true; fi; 'fail' || true", &mut session).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn alias_with_trailing_space_chains() {
+    let mut session = Session::new();
+
+    run("alias run='command ' fail=false nop=true", &mut session).unwrap();
+    run("alias command=", &mut session).unwrap();
+    assert_eq!(run("run fail", &mut session).unwrap(), 1);
+    assert_eq!(run("nop fail", &mut session).unwrap(), 0);
+}
+
+#[test]
+fn recursive_alias_stops() {
+    let mut session = Session::new();
+
+    run("alias true='true && true' a=b b=a", &mut session).unwrap();
+    assert_eq!(run("true", &mut session).unwrap(), 0);
+    assert_eq!(run("a", &mut session).unwrap(), 127);
+}
+
+#[test]
+fn alias_listing_and_removal() {
+    let path = temp_file("alias_listing");
+    let mut session = Session::new();
+
+    run("alias ll='ls -la' q=\"it's\"", &mut session).unwrap();
+    let input = format!("alias > {0}; type ll >> {0}", path.display());
+    run(&input, &mut session).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "alias ll='ls -la'\nalias q='it'\\''s'\nll is aliased to `ls -la`\n"
+    );
+
+    assert_eq!(run("alias nosuch", &mut session).unwrap(), 1);
+    assert_eq!(run("unalias ll", &mut session).unwrap(), 0);
+    assert_eq!(run("unalias ll", &mut session).unwrap(), 1);
+
+    run("unalias -a", &mut session).unwrap();
+    assert!(session.aliases.is_empty());
+
+    fs::remove_file(path).unwrap();
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "shell_interpreter_test_{}_{}",
+        name,
+        std::process::id()
+    ))
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_file(name);
+
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "").unwrap();
+    fs::write(dir.join("b.txt"), "").unwrap();
+
+    dir
+}
+
+fn session_with(variables: &[(&str, &str)]) -> Session {
+    let mut session = Session::new();
+
+    for (name, value) in variables {
+        session.variables.set(name, value).unwrap();
+    }
+
+    session
+}
+
+fn run(input: &str, session: &mut Session) -> Result<i32, std::io::Error> {
+    let tokens = Tokenizer::tokenize(input)?;
+
+    Interpreter::run::<CommandProvider>(&tokens, session)
+}
+
+fn assert_status(input: &str, expected: i32) {
+    let mut session = Session::new();
+
+    match run(input, &mut session) {
+        Ok(status) => assert_eq!(status, expected, "{}", input),
+        Err(err) => panic!("Unexpected error: {}", err),
+    }
+}
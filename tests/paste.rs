@@ -0,0 +1,90 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use shell_starter_rust::shell::{
+    editor::Editor,
+    paste::{expand_tabs, Paste},
+};
+
+#[test]
+fn insert_at_cursor() {
+    let mut paste = Paste::new();
+    let mut line = editor("echo  done");
+    line.move_to(5);
+
+    paste.insert(&mut line, "one two");
+    assert_eq!((line.text(), line.cursor()), ("echo one two done", 12));
+    assert_eq!(paste.highlight(), Some(5..12));
+
+    assert!(paste.clear_highlight());
+    assert!(!paste.clear_highlight());
+    assert_eq!(paste.highlight(), None);
+}
+
+#[test]
+fn line_breaks_become_line_feeds() {
+    let mut paste = Paste::new();
+    let mut line = Editor::new();
+
+    paste.insert(&mut line, "a\r\nb\rc\nd");
+    assert_eq!(line.text(), "a\nb\nc\nd");
+}
+
+#[test]
+fn control_characters_are_dropped() {
+    let mut paste = Paste::new();
+    let mut line = Editor::new();
+
+    paste.insert(&mut line, "echo a\x1b[2J\x07\tb\x00\nc");
+    assert_eq!(line.text(), "echo a[2J\tb\nc");
+    assert_eq!(expand_tabs(line.text()), "echo a[2J    b\nc");
+}
+
+#[test]
+fn paste_never_submits() {
+    let mut paste = Paste::new();
+    let mut line = Editor::new();
+
+    // The trailing line break would be an Enter, it's dropped instead.
+    paste.insert(&mut line, "ls\r\n");
+    assert_eq!(line.text(), "ls");
+    assert!(!paste.is_confirming());
+    assert_eq!(paste.confirm(&line, true), None);
+
+    paste.insert(&mut line, "\npwd\n\n");
+    assert_eq!((line.text(), line.cursor()), ("ls\npwd", 6));
+    assert!(!paste.is_confirming());
+}
+
+#[test]
+fn confirm_before_running_pasted_lines() {
+    let mut paste = Paste::new();
+    let mut line = Editor::new();
+
+    paste.insert(&mut line, "cd /tmp\nls\n");
+    assert_eq!(paste.confirm(&line, false), None);
+    assert!(!paste.is_confirming());
+
+    assert_eq!(
+        paste.confirm(&line, true).as_deref(),
+        Some("Run the 2 pasted lines? [y/N] ")
+    );
+    assert!(paste.is_confirming());
+
+    // Anything but `y` goes back to editing, and Enter asks again.
+    assert!(!paste.answer(KeyEvent::from(KeyCode::Enter)));
+    assert!(!paste.is_confirming());
+    assert!(paste.confirm(&line, true).is_some());
+    assert!(paste.answer(KeyEvent::from(KeyCode::Char('y'))));
+    assert!(!paste.is_confirming());
+
+    // Once it ran, typed lines don't ask.
+    paste.reset();
+    line.set("echo a\necho b");
+    assert_eq!(paste.confirm(&line, true), None);
+}
+
+fn editor(text: &str) -> Editor {
+    let mut line = Editor::new();
+    line.set(text);
+
+    line
+}
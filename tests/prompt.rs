@@ -0,0 +1,85 @@
+use shell_starter_rust::{
+    interpreter::Interpreter,
+    provider::CommandProvider,
+    shell::{
+        prompt::{self, Prompt},
+        session::Session,
+        variables::Variables,
+    },
+    tokenizer::Token,
+};
+
+#[test]
+fn expand_escapes() {
+    let mut session = session();
+    session.last_status = 3;
+
+    let prompt = expand("\\u:\\?:\\s:\\\\:\\j:\\x", &mut session);
+    assert_eq!(prompt.text(), "alice:3:shell:\\:0:\\x");
+
+    let prompt = expand("\\$ ", &mut session);
+    assert!(prompt.text() == "$ " || prompt.text() == "# ");
+
+    let prompt = expand("\\t|\\A", &mut session);
+    assert_eq!(prompt.text().len(), "00:00:00|00:00".len());
+}
+
+#[test]
+fn escaped_values_are_not_expanded() {
+    let mut session = session();
+    session.variables.set("USER", "$HOME`x`").unwrap();
+
+    assert_eq!(expand("\\u", &mut session).text(), "$HOME`x`");
+}
+
+#[test]
+fn expand_parameters_and_substitutions() {
+    let mut session = session();
+    session.variables.set("x", "value").unwrap();
+
+    let prompt = expand("[$x $(echo sub)] ${missing:-none} ", &mut session);
+    assert_eq!(prompt.text(), "[value sub] none ");
+    assert_eq!(prompt.width(), 17);
+
+    // A broken expansion leaves the prompt as it is.
+    assert_eq!(expand("${x ", &mut session).text(), "${x ");
+}
+
+#[test]
+fn colors_take_no_room() {
+    let mut session = session();
+
+    let prompt = expand("\\[\\e[1;32m\\]\\u\\[\\e[0m\\] \\$ ", &mut session);
+    assert_eq!(prompt.width(), "alice $ ".len());
+    assert!(prompt.text().starts_with("\x1b[1;32malice\x1b[0m"));
+
+    let prompt = expand("\\033[31m>\\033[0m ", &mut session);
+    assert_eq!(prompt.text(), "\x1b[31m>\x1b[0m ");
+    assert_eq!(prompt.width(), 2);
+
+    assert_eq!(prompt::display_width("\x1b]0;title\x07$ "), 2);
+    assert_eq!(prompt::display_width("\x01\x1b[1m\x02日本 ❯ "), 7);
+}
+
+#[test]
+fn width_of_last_line() {
+    let mut session = session();
+
+    let prompt = expand("\\u on a long first line\\n> ", &mut session);
+    assert_eq!(prompt.text(), "alice on a long first line\n> ");
+    assert_eq!(prompt.last_line(), "> ");
+    assert_eq!(prompt.width(), 2);
+}
+
+fn session() -> Session {
+    let mut session = Session::new();
+    session.variables = Variables::default();
+    session.variables.set("USER", "alice").unwrap();
+    session.name = String::from("/bin/shell");
+
+    session
+}
+
+fn expand(template: &str, session: &mut Session) -> Prompt {
+    Prompt::new::<Token, Interpreter, CommandProvider>(template, session)
+}
@@ -0,0 +1,209 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+use shell_starter_rust::{
+    interpreter::Interpreter,
+    provider::CommandProvider,
+    shell::{
+        args::{Arguments, Mode},
+        core::{ShellInterpreter, ShellTokenizer},
+        script::{self, Script},
+        session::Session,
+    },
+    tokenizer::{Token, Tokenizer},
+};
+
+// Arguments
+
+#[test]
+fn command_string_with_name_and_arguments() {
+    let arguments = parse(&["shell", "-c", "echo $1", "name", "a", "b"]);
+
+    assert_eq!(arguments.mode, Mode::Command("echo $1".to_string()));
+    assert_eq!(arguments.name, "name");
+    assert_eq!(arguments.positional, vec!["a", "b"]);
+}
+
+#[test]
+fn script_path_with_arguments() {
+    let arguments = parse(&["shell", "run.sh", "-x", "y"]);
+
+    assert_eq!(arguments.mode, Mode::Script(PathBuf::from("run.sh")));
+    assert_eq!(arguments.name, "run.sh");
+    assert_eq!(arguments.positional, vec!["-x", "y"]);
+}
+
+#[test]
+fn invalid_arguments() {
+    assert!(Arguments::parse(args(&["shell", "-q"])).is_err());
+    assert!(Arguments::parse(args(&["shell", "-c"])).is_err());
+}
+
+#[test]
+fn startup_options() {
+    let arguments = parse(&["shell", "--norc", "-l", "--rcfile", "/etc/rc", "-c", "true"]);
+
+    assert!(arguments.norc && arguments.login);
+    assert_eq!(arguments.rcfile, Some(PathBuf::from("/etc/rc")));
+    assert!(Arguments::parse(args(&["shell", "--rcfile"])).is_err());
+}
+
+#[test]
+fn startup_files() {
+    let arguments = parse(&["shell", "--rcfile", "/nonexistent/rc", "-c", "true"]);
+    assert_eq!(
+        arguments.startup_files(),
+        vec![PathBuf::from("/nonexistent/rc")]
+    );
+
+    let arguments = parse(&["shell", "--norc", "-c", "true"]);
+    assert!(arguments.startup_files().is_empty());
+}
+
+// Running
+
+#[test]
+fn status_of_last_command() {
+    assert_eq!(run("true\nfalse"), 1);
+    assert_eq!(run("false; true\n"), 0);
+}
+
+#[test]
+fn exit_ends_script() {
+    assert_eq!(run("exit 3\nexit 4"), 3);
+    assert_eq!(run("false\nexit"), 1);
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    assert_eq!(run("#!/bin/sh\n\n# a comment\ntest a = a # trailing\n"), 0);
+}
+
+#[test]
+fn command_spans_lines() {
+    assert_eq!(run("grep -q body <<EOF\nthe body\nEOF\n"), 0);
+}
+
+#[test]
+fn compound_command_spans_lines() {
+    assert_eq!(run("for i in a b\ndo\n  test $i = b && exit 5\ndone\n"), 5);
+    assert_eq!(run("if false\nthen\n  true\nelse\n  exit 4\nfi"), 4);
+}
+
+#[test]
+fn syntax_error_ends_script() {
+    assert_eq!(run("ls | | wc\ntrue"), 2);
+    assert_eq!(run("cat <<EOF\nno end"), 2);
+    assert_eq!(run("while true\ndo true"), 2);
+}
+
+#[test]
+fn unset_parameter_error_ends_script() {
+    assert_eq!(run("echo ${U:?missing}; true\ntrue"), 1);
+    assert_eq!(run("f() { : ${U:?}; }\nf\nexit 0"), 1);
+    assert_eq!(run("echo ${U:-set}\ntrue"), 0);
+}
+
+#[test]
+fn command_not_found_without_path() {
+    let output = Command::new(env!("CARGO_BIN_EXE_shell-starter-rust"))
+        .args(["--norc", "-c", "unset PATH; no_such_command; echo $?"])
+        .output()
+        .unwrap();
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "127\n");
+}
+
+#[test]
+fn positional_parameters() {
+    let mut session = Session::new();
+    session.positional = vec!["a".to_string(), "b c".to_string()];
+
+    let status = Script::new(session).run::<Token, Interpreter, Tokenizer, CommandProvider>(
+        "test $# = 2 && test \"$2\" = 'b c' && test \"$*\" = 'a b c'".as_bytes(),
+    );
+
+    assert_eq!(status, 0);
+}
+
+// Sourcing
+
+#[test]
+fn source_runs_in_session() {
+    let path = temp_file("source_session");
+    fs::write(&path, "f() { x=$1; }\nf \"$2\"\nalias a=true\n").unwrap();
+    let mut session = Session::new();
+
+    let input = format!("source {} one two; . {}", path.display(), path.display());
+    assert_eq!(run_in(&input, &mut session), 0);
+    assert_eq!(session.variables.get("x"), Some(""));
+    assert!(session.functions.contains_key("f"));
+    assert!(session.aliases.contains_key("a"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn source_with_arguments_and_return() {
+    let path = temp_file("source_return");
+    fs::write(&path, "x=$#:$1\nreturn 3\nx=unreachable\n").unwrap();
+    let mut session = Session::new();
+    session.positional = vec!["outer".to_string()];
+
+    assert_eq!(
+        run_in(&format!("source {} a", path.display()), &mut session),
+        3
+    );
+    assert_eq!(session.variables.get("x"), Some("1:a"));
+    assert_eq!(session.positional, vec!["outer"]);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn source_reports_errors_and_goes_on() {
+    let path = temp_file("source_errors");
+    fs::write(&path, "x=1\nif then\nno_such_command_here\ny=2\n").unwrap();
+    let mut session = Session::new();
+
+    script::source::<Token, Interpreter, Tokenizer, CommandProvider>(&mut session, &path).unwrap();
+    assert_eq!(session.variables.get("x"), Some("1"));
+    assert_eq!(session.variables.get("y"), Some("2"));
+
+    assert_eq!(run_in("source /nonexistent/file", &mut session), 1);
+    assert_eq!(run_in("source", &mut session), 1);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn exit_in_sourced_file() {
+    let path = temp_file("source_exit");
+    fs::write(&path, "exit 6\n").unwrap();
+
+    assert_eq!(run(&format!("source {}\nexit 1", path.display())), 6);
+
+    fs::remove_file(path).unwrap();
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("shell_script_test_{}_{}", name, std::process::id()))
+}
+
+fn run_in(input: &str, session: &mut Session) -> i32 {
+    let tokens = Tokenizer::tokenize(input).unwrap();
+
+    Interpreter::run::<CommandProvider>(&tokens, session).unwrap()
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn parse(arguments: &[&str]) -> Arguments {
+    Arguments::parse(args(arguments)).unwrap()
+}
+
+fn run(input: &str) -> i32 {
+    Script::new(Session::new())
+        .run::<Token, Interpreter, Tokenizer, CommandProvider>(input.as_bytes())
+}
@@ -3,7 +3,12 @@ use std::io::ErrorKind;
 use shell_starter_rust::{
     shell::core::ShellTokenizer,
     tokenizer::{
-        Token::{self, Appender, Argument, Redirector, Space, String, Value},
+        Token::{
+            self, And, Appender, Argument, Background, BothAppender, BothRedirector, Clobber,
+            CloseParen, DoubleSemicolon, Duplicator, HereDoc, HereString, InputDuplicator,
+            OpenParen, Or, Pipe, ReadWriter, Reader, Redirector, Semicolon, Space, String,
+            Substitution, Value, Variable,
+        },
         Tokenizer,
     },
 };
@@ -58,7 +63,7 @@ fn single_quote() {
 
 #[test]
 fn unclosed_single_quote() {
-    assert_parsing_err("echo 'unclosed single quote");
+    assert_incomplete("echo 'unclosed single quote");
 }
 
 #[test]
@@ -77,7 +82,7 @@ fn double_quote() {
 fn unclosed_double_quote() {
     let input = "echo \"unclosed double quote";
 
-    assert_parsing_err(input);
+    assert_incomplete(input);
 }
 
 #[test]
@@ -140,6 +145,20 @@ fn single_inside_double_quote() {
     assert_parsing(input, expected);
 }
 
+#[test]
+fn escaped_quote_after_apostrophe_in_double_quote() {
+    let input = "echo \"it's \\\"x\\\"\" 'y'";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        String("it's \"x\"".to_string(), true),
+        Space,
+        String("y".to_string(), false),
+    ];
+
+    assert_parsing(input, expected);
+}
+
 #[test]
 fn mixed_quotes_and_arguments() {
     let input = "cmd 'single' \"double\" --arg1 -a";
@@ -196,7 +215,7 @@ fn redirection_operator() {
         Space,
         String("hello world".to_string(), true),
         Space,
-        Redirector('1'),
+        Redirector(1),
         Space,
         String("./hello.md".to_string(), true),
     ];
@@ -212,7 +231,7 @@ fn error_redirection_operator() {
         Space,
         String("hello world".to_string(), true),
         Space,
-        Redirector('2'),
+        Redirector(2),
         Space,
         String("./hello.md".to_string(), true),
     ];
@@ -225,6 +244,40 @@ fn redirection_without_target() {
     assert_parsing_err("echo >");
 }
 
+#[test]
+fn descriptor_above_nine() {
+    assert_parsing(
+        "echo 10> file",
+        vec![
+            Value("echo".to_string()),
+            Space,
+            Redirector(10),
+            Space,
+            Value("file".to_string()),
+        ],
+    );
+    assert_parsing(
+        "cat 12<&0",
+        vec![
+            Value("cat".to_string()),
+            Space,
+            InputDuplicator(12),
+            Value("0".to_string()),
+        ],
+    );
+    assert_parsing_err("echo 99999999999999999999999> file");
+    assert_parsing(
+        "echo a1>b",
+        vec![
+            Value("echo".to_string()),
+            Space,
+            Value("a1".to_string()),
+            Redirector(1),
+            Value("b".to_string()),
+        ],
+    );
+}
+
 #[test]
 fn appender() {
     let input = "echo \"hello world\" >> \"./hello.md\"";
@@ -233,7 +286,7 @@ fn appender() {
         Space,
         String("hello world".to_string(), true),
         Space,
-        Appender('1'),
+        Appender(1),
         Space,
         String("./hello.md".to_string(), true),
     ];
@@ -249,7 +302,7 @@ fn appender_with_number() {
         Space,
         String("hello world".to_string(), true),
         Space,
-        Appender('2'),
+        Appender(2),
         Space,
         String("./hello.md".to_string(), true),
     ];
@@ -257,11 +310,421 @@ fn appender_with_number() {
     assert_parsing(input, expected);
 }
 
+// Pipes
+
+#[test]
+fn pipe() {
+    let input = "ls -la | wc -l";
+    let expected = vec![
+        Value("ls".to_string()),
+        Space,
+        Argument("la".to_string(), false),
+        Space,
+        Pipe,
+        Space,
+        Value("wc".to_string()),
+        Space,
+        Argument("l".to_string(), false),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn pipe_without_spaces() {
+    let input = "cat 'file'|sort|uniq";
+    let expected = vec![
+        Value("cat".to_string()),
+        Space,
+        String("file".to_string(), false),
+        Pipe,
+        Value("sort".to_string()),
+        Pipe,
+        Value("uniq".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+// Lists
+
+#[test]
+fn list_operators() {
+    let input = "make && ./run || echo failed; pwd";
+    let expected = vec![
+        Value("make".to_string()),
+        Space,
+        And,
+        Space,
+        Value("./run".to_string()),
+        Space,
+        Or,
+        Space,
+        Value("echo".to_string()),
+        Space,
+        Value("failed".to_string()),
+        Semicolon,
+        Space,
+        Value("pwd".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn single_ampersand() {
+    let input = "sleep 1 & echo b&";
+    let expected = vec![
+        Value("sleep".to_string()),
+        Space,
+        Value("1".to_string()),
+        Space,
+        Background,
+        Space,
+        Value("echo".to_string()),
+        Space,
+        Value("b".to_string()),
+        Background,
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn comments_and_tabs() {
+    let input = "echo\ta#b # comment\n";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Value("a#b".to_string()),
+        Space,
+        Semicolon,
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn case_operators() {
+    let input = "case $x in (a|b) true;; *.rs) ! false;; esac";
+    let expected = vec![
+        Value("case".to_string()),
+        Space,
+        Variable("x".to_string(), false),
+        Space,
+        Value("in".to_string()),
+        Space,
+        OpenParen,
+        Value("a".to_string()),
+        Pipe,
+        Value("b".to_string()),
+        CloseParen,
+        Space,
+        Value("true".to_string()),
+        DoubleSemicolon,
+        Space,
+        Value("*.rs".to_string()),
+        CloseParen,
+        Space,
+        Value("!".to_string()),
+        Space,
+        Value("false".to_string()),
+        DoubleSemicolon,
+        Space,
+        Value("esac".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn last_status_variable() {
+    let input = "echo $? \"status: $?\"";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Variable("?".to_string(), false),
+        Space,
+        String("status: ".to_string(), true),
+        Variable("?".to_string(), true),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn named_variable() {
+    let input = "echo $HOME/bin";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Variable("HOME".to_string(), false),
+        Value("/bin".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn braced_parameter_expansion() {
+    let input = "echo ${NAME:-${OTHER}} \"${#NAME}\"";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Variable("NAME:-${OTHER}".to_string(), false),
+        Space,
+        Variable("#NAME".to_string(), true),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn unclosed_parameter_expansion() {
+    assert_incomplete("echo ${NAME");
+}
+
+#[test]
+fn quoted_assignment_value() {
+    let input = "NAME=\"a b\" cmd";
+    let expected = vec![
+        Value("NAME=".to_string()),
+        String("a b".to_string(), true),
+        Space,
+        Value("cmd".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn glob_characters() {
+    let input = "ls src/*.r? [!a-c]* \\*.txt";
+    let expected = vec![
+        Value("ls".to_string()),
+        Space,
+        Value("src/*.r?".to_string()),
+        Space,
+        Value("[!a-c]*".to_string()),
+        Space,
+        String("*".to_string(), false),
+        Value(".txt".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn command_substitution() {
+    let input = "cd $(git rev-parse --show-toplevel)/src \"$(echo \")\" (a))\"";
+    let expected = vec![
+        Value("cd".to_string()),
+        Space,
+        Substitution("git rev-parse --show-toplevel".to_string(), false),
+        Value("/src".to_string()),
+        Space,
+        Substitution("echo \")\" (a)".to_string(), true),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn case_in_command_substitution() {
+    let input = "echo $(case x in x) echo y;; (z) echo z;; esac)";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Substitution("case x in x) echo y;; (z) echo z;; esac".to_string(), false),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn backtick_substitution() {
+    let input = "echo `echo \\`date\\``";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Substitution("echo `date`".to_string(), false),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn unclosed_command_substitution() {
+    assert_incomplete("echo $(ls");
+    assert_incomplete("echo `ls");
+}
+
+#[test]
+fn input_redirection() {
+    let input = "wc -l < file.txt";
+    let expected = vec![
+        Value("wc".to_string()),
+        Space,
+        Argument("l".to_string(), false),
+        Space,
+        Reader(0),
+        Space,
+        Value("file.txt".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn here_document() {
+    let input = "cat <<EOF\nhello $USER\nEOF\necho done";
+    let expected = vec![
+        Value("cat".to_string()),
+        Space,
+        HereDoc("hello $USER\n".to_string(), true),
+        Semicolon,
+        Value("echo".to_string()),
+        Space,
+        Value("done".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn quoted_here_document_with_tabs() {
+    let input = "cat <<-'EOF'\n\t$HOME\n\tEOF";
+    let expected = vec![
+        Value("cat".to_string()),
+        Space,
+        HereDoc("$HOME\n".to_string(), false),
+        Semicolon,
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn unfinished_here_document() {
+    assert_incomplete("cat <<EOF\nhello");
+}
+
+#[test]
+fn line_continuation() {
+    let input = "echo a \\\nb\\\nc \"d\\\ne\"";
+    let expected = vec![
+        Value("echo".to_string()),
+        Space,
+        Value("a".to_string()),
+        Space,
+        Value("bc".to_string()),
+        Space,
+        String("de".to_string(), true),
+    ];
+
+    assert_parsing(input, expected);
+    assert_incomplete("echo a \\");
+    assert_incomplete("echo a\\");
+    assert_incomplete("echo \"a\\");
+}
+
+#[test]
+fn here_string() {
+    let input = "tr a-z A-Z <<< \"$HOME\"";
+    let expected = vec![
+        Value("tr".to_string()),
+        Space,
+        Value("a-z".to_string()),
+        Space,
+        Value("A-Z".to_string()),
+        Space,
+        HereString,
+        Space,
+        Variable("HOME".to_string(), true),
+    ];
+
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn descriptor_redirections() {
+    let input = "cmd 2>&1 >&- &> all &>> all 3<> rw <&3 >| out";
+    let expected = vec![
+        Value("cmd".to_string()),
+        Space,
+        Duplicator(2),
+        Value("1".to_string()),
+        Space,
+        Duplicator(1),
+        Argument("".to_string(), false),
+        Space,
+        BothRedirector,
+        Space,
+        Value("all".to_string()),
+        Space,
+        BothAppender,
+        Space,
+        Value("all".to_string()),
+        Space,
+        ReadWriter(3),
+        Space,
+        Value("rw".to_string()),
+        Space,
+        InputDuplicator(0),
+        Value("3".to_string()),
+        Space,
+        Clobber(1),
+        Space,
+        Value("out".to_string()),
+    ];
+
+    assert_parsing(input, expected);
+}
+
 #[test]
-fn invalid_character() {
-    let input = "echo hello @world";
+fn punctuation_in_words() {
+    let input = "ssh user@host; git show HEAD@{1}; grep ^FOO -x~!{} --opt=a@b^c\\ d";
+    let expected = vec![
+        Value("ssh".to_string()),
+        Space,
+        Value("user@host".to_string()),
+        Semicolon,
+        Space,
+        Value("git".to_string()),
+        Space,
+        Value("show".to_string()),
+        Space,
+        Value("HEAD@{1}".to_string()),
+        Semicolon,
+        Space,
+        Value("grep".to_string()),
+        Space,
+        Value("^FOO".to_string()),
+        Space,
+        Argument("x~!{}".to_string(), false),
+        Space,
+        Argument("opt=a@b^c d".to_string(), true),
+    ];
 
-    assert_parsing_err(input);
+    assert_parsing(input, expected);
+}
+
+#[test]
+fn unicode_words() {
+    let input = "cat 日本/naïve.txt --naïve -ñ 👍🏽";
+    let expected = vec![
+        Value("cat".to_string()),
+        Space,
+        Value("日本/naïve.txt".to_string()),
+        Space,
+        Argument("naïve".to_string(), true),
+        Space,
+        Argument("ñ".to_string(), false),
+        Space,
+        Value("👍🏽".to_string()),
+    ];
+
+    assert_parsing(input, expected);
 }
 
 #[test]
@@ -300,3 +763,9 @@ fn assert_parsing_err(input: &str) {
     assert!(result.is_err());
     assert_eq!(result.err().unwrap().kind(), ErrorKind::InvalidInput);
 }
+
+fn assert_incomplete(input: &str) {
+    let result = Tokenizer::tokenize(input);
+
+    assert_eq!(result.err().unwrap().kind(), ErrorKind::UnexpectedEof);
+}
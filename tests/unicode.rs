@@ -0,0 +1,49 @@
+use shell_starter_rust::util::unicode;
+
+#[test]
+fn graphemes() {
+    let text = "e\u{301}x👨\u{200D}👩\u{200D}👧🇯🇵🇫🇷각\u{1100}\u{1161}\u{11A8}\r\n";
+    let graphemes: Vec<&str> = unicode::graphemes(text)
+        .into_iter()
+        .map(|(_, grapheme)| grapheme)
+        .collect();
+
+    assert_eq!(
+        graphemes,
+        [
+            "e\u{301}",
+            "x",
+            "👨\u{200D}👩\u{200D}👧",
+            "🇯🇵",
+            "🇫🇷",
+            "각",
+            "\u{1100}\u{1161}\u{11A8}",
+            "\r\n"
+        ]
+    );
+}
+
+#[test]
+fn boundaries() {
+    let text = "a👍🏽b";
+
+    assert_eq!(unicode::next_boundary(text, 0), 1);
+    assert_eq!(unicode::next_boundary(text, 1), 1 + "👍🏽".len());
+    assert_eq!(unicode::previous_boundary(text, text.len() - 1), 1);
+    assert_eq!(unicode::previous_boundary(text, 1), 0);
+    assert_eq!(unicode::previous_boundary(text, 0), 0);
+    assert_eq!(unicode::next_boundary(text, text.len()), text.len());
+}
+
+#[test]
+fn width() {
+    assert_eq!(unicode::width("echo"), 4);
+    assert_eq!(unicode::width("日本語"), 6);
+    assert_eq!(unicode::width("ｅｃｈｏ"), 8);
+    assert_eq!(unicode::width("nai\u{308}ve"), 5);
+    assert_eq!(unicode::width("👍🏽 👨\u{200D}👩\u{200D}👧"), 5);
+    assert_eq!(unicode::width("🇯🇵"), 2);
+    assert_eq!(unicode::width("\u{2764}\u{FE0F}"), 2);
+    assert_eq!(unicode::width("\u{2764}"), 1);
+    assert_eq!(unicode::width("\t\x07"), 0);
+}
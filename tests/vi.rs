@@ -0,0 +1,178 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use shell_starter_rust::shell::{
+    editor::Editor,
+    history::History,
+    vi::{Mode, Outcome, Vi},
+};
+
+#[test]
+fn switch_modes() {
+    let mut vi = Vi::new();
+    let mut line = Editor::new();
+    let mut history = History::new(None, 10, 10);
+
+    type_keys(&mut vi, &mut line, &mut history, "echo hi");
+    assert_eq!(vi.mode(), Mode::Insert);
+
+    press(&mut vi, &mut line, &mut history, KeyCode::Esc);
+    assert_eq!(vi.mode(), Mode::Normal);
+    assert_eq!(line.cursor(), 6);
+
+    type_keys(&mut vi, &mut line, &mut history, "0A!");
+    assert_eq!(line.text(), "echo hi!");
+    assert_eq!(vi.mode(), Mode::Insert);
+
+    assert_eq!(
+        vi.handle(KeyEvent::from(KeyCode::Enter), &mut line, &mut history),
+        Outcome::Unhandled
+    );
+}
+
+#[test]
+fn motions() {
+    let (mut vi, mut line, mut history) = normal("git commit --amend");
+
+    type_keys(&mut vi, &mut line, &mut history, "0w");
+    assert_eq!(line.cursor(), 4);
+    type_keys(&mut vi, &mut line, &mut history, "e");
+    assert_eq!(line.cursor(), 9);
+    type_keys(&mut vi, &mut line, &mut history, "w");
+    assert_eq!(line.cursor(), 11);
+    type_keys(&mut vi, &mut line, &mut history, "b");
+    assert_eq!(line.cursor(), 4);
+    type_keys(&mut vi, &mut line, &mut history, "fa");
+    assert_eq!(line.cursor(), 13);
+    type_keys(&mut vi, &mut line, &mut history, "0tm");
+    assert_eq!(line.cursor(), 5);
+    type_keys(&mut vi, &mut line, &mut history, "$");
+    assert_eq!(line.cursor(), 17);
+    type_keys(&mut vi, &mut line, &mut history, "2h");
+    assert_eq!(line.cursor(), 15);
+
+    assert_eq!(
+        vi.handle(key('l'), &mut line, &mut history),
+        Outcome::Handled
+    );
+    type_keys(&mut vi, &mut line, &mut history, "l");
+    assert_eq!(vi.handle(key('l'), &mut line, &mut history), Outcome::Bell);
+    assert_eq!(vi.handle(key('q'), &mut line, &mut history), Outcome::Bell);
+}
+
+#[test]
+fn operators() {
+    let (mut vi, mut line, mut history) = normal("echo one two three");
+
+    type_keys(&mut vi, &mut line, &mut history, "0wdw");
+    assert_eq!(line.text(), "echo two three");
+
+    type_keys(&mut vi, &mut line, &mut history, "cwfour");
+    press(&mut vi, &mut line, &mut history, KeyCode::Esc);
+    assert_eq!(line.text(), "echo four three");
+
+    type_keys(&mut vi, &mut line, &mut history, "byw$p");
+    assert_eq!(line.text(), "echo four threefour ");
+
+    type_keys(&mut vi, &mut line, &mut history, "0dfr");
+    assert_eq!(line.text(), " threefour ");
+
+    type_keys(&mut vi, &mut line, &mut history, "xx");
+    assert_eq!(line.text(), "hreefour ");
+
+    type_keys(&mut vi, &mut line, &mut history, "D");
+    assert_eq!(line.text(), "");
+    type_keys(&mut vi, &mut line, &mut history, "P");
+    assert_eq!(line.text(), "hreefour ");
+
+    type_keys(&mut vi, &mut line, &mut history, "dd");
+    assert_eq!(line.text(), "");
+}
+
+#[test]
+fn huge_counts() {
+    let (mut vi, mut line, mut history) = normal("echo one two");
+
+    type_keys(
+        &mut vi,
+        &mut line,
+        &mut history,
+        "0w99999999999d99999999999w",
+    );
+    assert_eq!(line.text(), "echo ");
+
+    type_keys(&mut vi, &mut line, &mut history, "0yw99999999999p");
+    assert_eq!(line.text().len(), 10_005);
+
+    // A count too big for a number rings the bell.
+    let keys = "99999999999999999999999";
+    let outcomes: Vec<Outcome> = keys
+        .chars()
+        .map(|ch| vi.handle(key(ch), &mut line, &mut history))
+        .collect();
+    assert_eq!(outcomes[19], Outcome::Bell);
+}
+
+#[test]
+fn undo_and_repeat() {
+    let (mut vi, mut line, mut history) = normal("a b c d");
+
+    type_keys(&mut vi, &mut line, &mut history, "0dw..");
+    assert_eq!(line.text(), "d");
+
+    type_keys(&mut vi, &mut line, &mut history, "uu");
+    assert_eq!(line.text(), "b c d");
+
+    type_keys(&mut vi, &mut line, &mut history, "ix");
+    press(&mut vi, &mut line, &mut history, KeyCode::Esc);
+    type_keys(&mut vi, &mut line, &mut history, "w.");
+    assert_eq!(line.text(), "xb xc d");
+    assert_eq!(vi.mode(), Mode::Normal);
+
+    type_keys(&mut vi, &mut line, &mut history, "uuu");
+    assert_eq!(line.text(), "a b c d");
+}
+
+#[test]
+fn search_history() {
+    let (mut vi, mut line, mut history) = normal("");
+    history.push("make build");
+    history.push("ls");
+    history.push("make test");
+
+    type_keys(&mut vi, &mut line, &mut history, "/make");
+    assert_eq!(vi.query(), Some("make"));
+    press(&mut vi, &mut line, &mut history, KeyCode::Enter);
+    assert_eq!(line.text(), "make test");
+    assert_eq!(vi.query(), None);
+
+    type_keys(&mut vi, &mut line, &mut history, "n");
+    assert_eq!(line.text(), "make build");
+    assert_eq!(vi.handle(key('n'), &mut line, &mut history), Outcome::Bell);
+
+    type_keys(&mut vi, &mut line, &mut history, "k");
+    assert_eq!(line.text(), "make test");
+}
+
+fn normal(text: &str) -> (Vi, Editor, History) {
+    let mut vi = Vi::new();
+    let mut line = Editor::new();
+    let mut history = History::new(None, 10, 10);
+
+    line.set(text);
+    press(&mut vi, &mut line, &mut history, KeyCode::Esc);
+
+    (vi, line, history)
+}
+
+fn type_keys(vi: &mut Vi, line: &mut Editor, history: &mut History, keys: &str) {
+    for ch in keys.chars() {
+        vi.handle(key(ch), line, history);
+    }
+}
+
+fn press(vi: &mut Vi, line: &mut Editor, history: &mut History, code: KeyCode) {
+    vi.handle(KeyEvent::from(code), line, history);
+}
+
+fn key(ch: char) -> KeyEvent {
+    KeyEvent::from(KeyCode::Char(ch))
+}